@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Unable to write export file: {0}")]
+    UnableToWriteFile(#[from] std::io::Error),
+
+    #[error("Failed to serialize JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("The OSD doesn't show GPS coordinates")]
+    NoGpsData,
+}
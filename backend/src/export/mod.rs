@@ -0,0 +1,9 @@
+//! Write the data of the loaded files to formats that other tools can read.
+
+mod error;
+mod telemetry;
+mod track;
+
+pub use error::ExportError;
+pub use telemetry::{telemetry_records, write_telemetry, TelemetryFormat, TelemetryRecord};
+pub use track::{write_track, TrackFormat, TrackPoint};
@@ -0,0 +1,314 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use serde::Serialize;
+
+use super::error::ExportError;
+use crate::{
+    osd::{telemetry::Telemetry, OsdFile},
+    srt::{SrtFile, SrtFrameData},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryFormat {
+    Csv,
+    Json,
+}
+
+impl TelemetryFormat {
+    /// JSON for `.json` files, CSV for everything else.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// The values read from one OSD frame together with the SRT data that was on screen at the same time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TelemetryRecord {
+    #[serde(flatten)]
+    pub osd: Telemetry,
+    pub srt: Option<SrtFrameData>,
+}
+
+const CSV_COLUMNS: &[&str] = &[
+    "time_s",
+    "battery_voltage",
+    "current",
+    "consumed_mah",
+    "altitude_m",
+    "speed_kmh",
+    "latitude",
+    "longitude",
+    "flight_mode",
+    "rssi",
+    "link_quality",
+    "armed",
+    "srt_signal",
+    "srt_channel",
+    "srt_flight_time",
+    "srt_sky_bat",
+    "srt_ground_bat",
+    "srt_latency",
+    "srt_bitrate_mbps",
+    "srt_distance",
+    "srt_hz",
+    "srt_sp",
+    "srt_gp",
+    "srt_air_temp",
+    "srt_gnd_temp",
+    "srt_sty_mode",
+];
+
+impl TelemetryRecord {
+    fn csv_row(&self) -> Vec<String> {
+        let osd = &self.osd;
+        let srt = self.srt.as_ref();
+        vec![
+            format!("{:.3}", osd.time_millis as f32 / 1000.0),
+            csv_value(osd.battery_voltage),
+            csv_value(osd.current),
+            csv_value(osd.consumed_mah),
+            csv_value(osd.altitude_m),
+            csv_value(osd.speed_kmh),
+            csv_value(osd.latitude),
+            csv_value(osd.longitude),
+            csv_value(osd.flight_mode.as_ref()),
+            csv_value(osd.rssi),
+            csv_value(osd.link_quality),
+            csv_value(osd.armed),
+            csv_value(srt.and_then(|s| s.signal)),
+            csv_value(srt.and_then(|s| s.channel.as_ref())),
+            csv_value(srt.and_then(|s| s.flight_time)),
+            csv_value(srt.and_then(|s| s.sky_bat)),
+            csv_value(srt.and_then(|s| s.ground_bat)),
+            csv_value(srt.and_then(|s| s.latency)),
+            csv_value(srt.and_then(|s| s.bitrate_mbps)),
+            csv_value(srt.and_then(|s| s.distance)),
+            csv_value(srt.and_then(|s| s.hz)),
+            csv_value(srt.and_then(|s| s.sp)),
+            csv_value(srt.and_then(|s| s.gp)),
+            csv_value(srt.and_then(|s| s.air_temp)),
+            csv_value(srt.and_then(|s| s.gnd_temp)),
+            csv_value(srt.and_then(|s| s.sty_mode)),
+        ]
+    }
+}
+
+/// One record per OSD frame. Timestamps are in video time: OSD timestamps are multiplied by
+/// `osd_playback_speed_factor` like when rendering, so they line up with the SRT data. SRT frames are in time order, so
+/// the one on screen is found with a binary search.
+pub fn telemetry_records(
+    osd_file: &OsdFile,
+    srt_file: Option<&SrtFile>,
+    osd_playback_speed_factor: f32,
+) -> Vec<TelemetryRecord> {
+    osd_file
+        .telemetry()
+        .into_iter()
+        .map(|mut osd| {
+            osd.time_millis = (osd.time_millis as f32 * osd_playback_speed_factor).round() as u32;
+            let time_secs = osd.time_millis as f32 / 1000.0;
+            let srt = srt_file.and_then(|srt_file| {
+                let index = srt_file
+                    .frames
+                    .partition_point(|frame| frame.end_time_secs <= time_secs);
+                srt_file
+                    .frames
+                    .get(index)
+                    .filter(|frame| frame.start_time_secs <= time_secs)
+                    .and_then(|frame| frame.data.clone())
+            });
+            TelemetryRecord { osd, srt }
+        })
+        .collect()
+}
+
+#[tracing::instrument(skip(records), err)]
+pub fn write_telemetry(records: &[TelemetryRecord], path: &Path, format: TelemetryFormat) -> Result<(), ExportError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    match format {
+        TelemetryFormat::Csv => write_csv(records, &mut writer)?,
+        TelemetryFormat::Json => serde_json::to_writer_pretty(&mut writer, records)?,
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_csv(records: &[TelemetryRecord], writer: &mut impl Write) -> std::io::Result<()> {
+    writeln!(writer, "{}", CSV_COLUMNS.join(","))?;
+    for record in records {
+        writeln!(writer, "{}", record.csv_row().join(","))?;
+    }
+    Ok(())
+}
+
+/// Empty for missing values. Text is quoted when it contains characters that have a meaning in CSV.
+fn csv_value(value: Option<impl Display>) -> String {
+    let Some(value) = value else {
+        return String::new();
+    };
+    let value = value.to_string();
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use super::*;
+    use crate::{
+        osd::{FcFirmware, Frame, GridSize},
+        srt::SrtFrame,
+    };
+
+    fn srt_data(sky_bat: f32) -> SrtFrameData {
+        SrtFrameData {
+            signal: Some(4),
+            channel: None,
+            flight_time: None,
+            sky_bat: Some(sky_bat),
+            ground_bat: None,
+            latency: None,
+            bitrate_mbps: None,
+            distance: None,
+            hz: None,
+            sp: None,
+            gp: None,
+            air_temp: None,
+            gnd_temp: None,
+            sty_mode: None,
+        }
+    }
+
+    fn srt_file(frames: Vec<SrtFrame>) -> SrtFile {
+        SrtFile {
+            file_path: PathBuf::new(),
+            has_signal: true,
+            has_channel: false,
+            has_flight_time: false,
+            has_sky_bat: true,
+            has_ground_bat: false,
+            has_latency: false,
+            has_bitrate: false,
+            has_distance: false,
+            has_hz: false,
+            has_sp: false,
+            has_gp: false,
+            has_air_temp: false,
+            has_gnd_temp: false,
+            has_sty_mode: false,
+            has_debug: false,
+            duration: Duration::from_secs(2),
+            frames,
+        }
+    }
+
+    #[test]
+    fn merge_srt_data_by_video_time() {
+        let frames = [0, 500, 1000]
+            .into_iter()
+            .map(|time_millis| Frame {
+                time_millis,
+                glyphs: Vec::new(),
+            })
+            .collect();
+        let osd_file = OsdFile::from_frames(
+            PathBuf::new(),
+            "Walksnail",
+            FcFirmware::Betaflight,
+            GridSize::HD,
+            frames,
+        );
+        let srt_file = srt_file(vec![
+            SrtFrame {
+                start_time_secs: 0.0,
+                end_time_secs: 1.0,
+                data: Some(srt_data(16.8)),
+                debug_data: None,
+            },
+            SrtFrame {
+                start_time_secs: 1.0,
+                end_time_secs: 1.5,
+                data: Some(srt_data(15.2)),
+                debug_data: None,
+            },
+            SrtFrame {
+                start_time_secs: 2.0,
+                end_time_secs: 2.5,
+                data: Some(srt_data(14.9)),
+                debug_data: None,
+            },
+        ]);
+
+        let records = telemetry_records(&osd_file, Some(&srt_file), 1.2);
+        assert_eq!(
+            records.iter().map(|r| r.osd.time_millis).collect::<Vec<_>>(),
+            [0, 600, 1200]
+        );
+        assert_eq!(
+            records
+                .iter()
+                .map(|r| r.srt.as_ref().and_then(|s| s.sky_bat))
+                .collect::<Vec<_>>(),
+            [Some(16.8), Some(16.8), Some(15.2)]
+        );
+
+        // No SRT data in the gap between frames
+        let records = telemetry_records(&osd_file, Some(&srt_file), 1.8);
+        assert_eq!(
+            records
+                .iter()
+                .map(|r| r.srt.as_ref().and_then(|s| s.sky_bat))
+                .collect::<Vec<_>>(),
+            [Some(16.8), Some(16.8), None]
+        );
+    }
+
+    #[test]
+    fn csv_rows() {
+        let record = TelemetryRecord {
+            osd: Telemetry {
+                time_millis: 1250,
+                battery_voltage: Some(16.2),
+                flight_mode: Some("A,B".into()),
+                armed: Some(true),
+                ..Default::default()
+            },
+            srt: None,
+        };
+
+        let mut csv = Vec::new();
+        write_csv(&[record], &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next().unwrap().split(',').count(), CSV_COLUMNS.len());
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("1.250,16.2,,,,,,,\"A,B\",,,true{}", ",".repeat(14))
+        );
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(
+            TelemetryFormat::from_path(Path::new("flight.JSON")),
+            TelemetryFormat::Json
+        );
+        assert_eq!(
+            TelemetryFormat::from_path(Path::new("flight.csv")),
+            TelemetryFormat::Csv
+        );
+        assert_eq!(TelemetryFormat::from_path(Path::new("flight")), TelemetryFormat::Csv);
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::error::ExportError;
+use crate::osd::OsdFile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackFormat {
+    Gpx,
+    Kml,
+}
+
+impl TrackFormat {
+    /// KML for `.kml` files, GPX for everything else.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("kml") => Self::Kml,
+            _ => Self::Gpx,
+        }
+    }
+}
+
+/// A GPS position shown on the OSD. The altitude is the one on the OSD, which flight controllers show relative to the
+/// home position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackPoint {
+    pub time_millis: u32,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude_m: Option<f32>,
+}
+
+impl OsdFile {
+    /// The GPS positions on the OSD. Frames without a GPS fix, which show `0.0` coordinates, and frames where the
+    /// position didn't change are left out.
+    pub fn flight_path(&self) -> Vec<TrackPoint> {
+        let mut track: Vec<TrackPoint> = Vec::new();
+        for telemetry in self.telemetry() {
+            let (Some(latitude), Some(longitude)) = (telemetry.latitude, telemetry.longitude) else {
+                continue;
+            };
+            let valid = latitude.abs() <= 90.0 && longitude.abs() <= 180.0 && (latitude, longitude) != (0.0, 0.0);
+            if !valid {
+                continue;
+            }
+            let point = TrackPoint {
+                time_millis: telemetry.time_millis,
+                latitude,
+                longitude,
+                altitude_m: telemetry.altitude_m,
+            };
+            if track.last().is_some_and(|last| {
+                (last.latitude, last.longitude, last.altitude_m) == (point.latitude, point.longitude, point.altitude_m)
+            }) {
+                continue;
+            }
+            track.push(point);
+        }
+        track
+    }
+
+    /// When the recording started, assuming the file was last written when it ended. The OSD formats don't store the
+    /// time of day so this is as close as it gets.
+    pub fn recording_start(&self) -> SystemTime {
+        fs::metadata(&self.file_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.checked_sub(self.duration))
+            .unwrap_or(UNIX_EPOCH)
+    }
+}
+
+/// Write the track with each point timed at `start` plus the OSD timestamp.
+#[tracing::instrument(skip(track), err)]
+pub fn write_track(
+    track: &[TrackPoint],
+    name: &str,
+    start: SystemTime,
+    path: &Path,
+    format: TrackFormat,
+) -> Result<(), ExportError> {
+    if track.is_empty() {
+        return Err(ExportError::NoGpsData);
+    }
+    let mut bytes = Vec::new();
+    match format {
+        TrackFormat::Gpx => write_gpx(track, name, start, &mut bytes)?,
+        TrackFormat::Kml => write_kml(track, name, start, &mut bytes)?,
+    }
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// GPX elevations are above sea level, so the altitude relative to home is left out.
+fn write_gpx(track: &[TrackPoint], name: &str, start: SystemTime, writer: &mut impl Write) -> std::io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<gpx version="1.1" creator="{}" xmlns="http://www.topografix.com/GPX/1/1">"#,
+        crate::NAMESPACE
+    )?;
+    writeln!(writer, "  <trk>")?;
+    writeln!(writer, "    <name>{}</name>", escape_xml(name))?;
+    writeln!(writer, "    <trkseg>")?;
+    for point in track {
+        writeln!(
+            writer,
+            r#"      <trkpt lat="{:.7}" lon="{:.7}">"#,
+            point.latitude, point.longitude
+        )?;
+        writeln!(writer, "        <time>{}</time>", timestamp(start, point.time_millis))?;
+        writeln!(writer, "      </trkpt>")?;
+    }
+    writeln!(writer, "    </trkseg>")?;
+    writeln!(writer, "  </trk>")?;
+    writeln!(writer, "</gpx>")
+}
+
+/// A `gx:Track` extruded to the ground, with the altitude relative to the ground like the OSD shows it.
+fn write_kml(track: &[TrackPoint], name: &str, start: SystemTime, writer: &mut impl Write) -> std::io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">"#
+    )?;
+    writeln!(writer, "  <Placemark>")?;
+    writeln!(writer, "    <name>{}</name>", escape_xml(name))?;
+    writeln!(writer, "    <gx:Track>")?;
+    writeln!(writer, "      <extrude>1</extrude>")?;
+    writeln!(writer, "      <altitudeMode>relativeToGround</altitudeMode>")?;
+    for point in track {
+        writeln!(writer, "      <when>{}</when>", timestamp(start, point.time_millis))?;
+    }
+    for point in track {
+        writeln!(
+            writer,
+            "      <gx:coord>{:.7} {:.7} {:.1}</gx:coord>",
+            point.longitude,
+            point.latitude,
+            point.altitude_m.unwrap_or_default()
+        )?;
+    }
+    writeln!(writer, "    </gx:Track>")?;
+    writeln!(writer, "  </Placemark>")?;
+    writeln!(writer, "</kml>")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// UTC time in the ISO 8601 format that GPX and KML use, e.g. `2024-05-01T12:30:00.250Z`.
+fn timestamp(start: SystemTime, time_millis: u32) -> String {
+    let time = start + Duration::from_millis(time_millis.into());
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let secs_of_day = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Year, month and day of the number of days since 1970-01-01, see
+/// <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::osd::{FcFirmware, Frame, Glyph, GridPosition, GridSize};
+
+    /// A Betaflight frame showing the text after the latitude and longitude symbols.
+    fn gps_frame(time_millis: u32, latitude: &str, longitude: &str) -> Frame {
+        let mut glyphs = Vec::new();
+        for (y, symbol, text) in [(0, 0x89, latitude), (1, 0x98, longitude)] {
+            let indices = std::iter::once(symbol).chain(text.bytes().map(u16::from));
+            for (x, index) in indices.enumerate() {
+                glyphs.push(Glyph {
+                    index,
+                    grid_position: GridPosition { x: x as u32, y },
+                });
+            }
+        }
+        Frame { time_millis, glyphs }
+    }
+
+    #[test]
+    fn flight_path_from_gps_elements() {
+        let frames = vec![
+            gps_frame(0, "0.0000000", "0.0000000"),
+            gps_frame(100, "47.3977419", "-8.5455938"),
+            gps_frame(200, "47.3977419", "-8.5455938"),
+            gps_frame(300, "47.3978000", "-8.5456000"),
+        ];
+        let osd_file = OsdFile::from_frames(
+            PathBuf::new(),
+            "Walksnail",
+            FcFirmware::Betaflight,
+            GridSize::HD,
+            frames,
+        );
+
+        let track = osd_file.flight_path();
+        assert_eq!(track.len(), 2);
+        assert_eq!(track[0].time_millis, 100);
+        assert_eq!(track[0].latitude, 47.3977419);
+        assert_eq!(track[0].longitude, -8.5455938);
+        assert_eq!(track[1].time_millis, 300);
+    }
+
+    #[test]
+    fn gpx_and_kml_output() {
+        let track = [TrackPoint {
+            time_millis: 1500,
+            latitude: 47.3977419,
+            longitude: -8.5455938,
+            altitude_m: Some(12.5),
+        }];
+        let start = UNIX_EPOCH + Duration::from_secs(1_714_566_600);
+
+        let mut gpx = Vec::new();
+        write_gpx(&track, "A & B", start, &mut gpx).unwrap();
+        let gpx = String::from_utf8(gpx).unwrap();
+        assert!(gpx.contains("<name>A &amp; B</name>"));
+        assert!(gpx.contains(r#"<trkpt lat="47.3977419" lon="-8.5455938">"#));
+        assert!(!gpx.contains("<ele>"));
+        assert!(gpx.contains("<time>2024-05-01T12:30:01.500Z</time>"));
+
+        let mut kml = Vec::new();
+        write_kml(&track, "flight", start, &mut kml).unwrap();
+        let kml = String::from_utf8(kml).unwrap();
+        assert!(kml.contains("<extrude>1</extrude>"));
+        assert!(kml.contains("<when>2024-05-01T12:30:01.500Z</when>"));
+        assert!(kml.contains("<gx:coord>-8.5455938 47.3977419 12.5</gx:coord>"));
+    }
+
+    #[test]
+    fn dates_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_844), (2024, 5, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    }
+}
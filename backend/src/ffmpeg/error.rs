@@ -28,4 +28,6 @@ pub enum FfmpegError {
         #[from]
         source: std::io::Error,
     },
+    #[error("Failed to create output directory: {0}")]
+    FailedToCreateOutputDirectory(std::io::Error),
 }
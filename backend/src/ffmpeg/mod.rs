@@ -8,7 +8,8 @@ mod video_info;
 
 pub use dependencies::{ffmpeg_available, ffprobe_available};
 pub use encoders::{Codec, Encoder};
+pub use error::{FfmpegError, VideoInfoError};
 pub use message::{FromFfmpegMessage, ToFfmpegMessage};
 pub use render::{handle_decoder_events, start_video_render};
-pub use render_settings::{RenderSettings, UpscaleTarget};
+pub use render_settings::{AudioMode, OutputFormat, RenderSettings, TimeRange, UpscaleTarget};
 pub use video_info::VideoInfo;
@@ -1,4 +1,4 @@
-use std::{io::Write, path::PathBuf, thread};
+use std::{fs, io::Write, path::PathBuf, thread};
 
 use crossbeam_channel::{Receiver, Sender};
 use ffmpeg_sidecar::{
@@ -6,33 +6,61 @@ use ffmpeg_sidecar::{
     command::FfmpegCommand,
     event::{FfmpegEvent, LogLevel},
 };
+use image::Rgba;
 
 use super::{
-    error::FfmpegError, render_settings::RenderSettings, Encoder, FromFfmpegMessage, ToFfmpegMessage, UpscaleTarget,
-    VideoInfo,
+    error::FfmpegError, render_settings::RenderSettings, AudioMode, Encoder, FromFfmpegMessage, OutputFormat,
+    TimeRange, ToFfmpegMessage, UpscaleTarget, VideoInfo,
 };
 use crate::{
     font,
     osd::{self, OsdOptions},
-    overlay::FrameOverlayIter,
+    overlay::{FrameOverlayIter, FrameSource, GeneratedFrames},
     srt::{self, SrtOptions},
 };
 
+/// Render the OSD and SRT data onto `input_video`. When the input video is not shown in the output (chroma key and
+/// transparent output formats) or there is no input video, frames are generated with the size and frame rate from
+/// `video_info` instead of decoding the video. The OSD is only rendered when there are OSD frames and a font.
 #[tracing::instrument(skip(osd_frames, srt_frames, font_file), err)]
 pub fn start_video_render(
     ffmpeg_path: &PathBuf,
-    input_video: &PathBuf,
+    input_video: Option<&PathBuf>,
     output_video: &PathBuf,
     osd_frames: Vec<osd::Frame>,
+    osd_grid_size: osd::GridSize,
     srt_frames: Vec<srt::SrtFrame>,
-    font_file: font::FontFile,
+    font_file: Option<font::FontFile>,
     srt_font: rusttype::Font<'static>,
     osd_options: &OsdOptions,
     srt_options: &SrtOptions,
     video_info: &VideoInfo,
     render_settings: &RenderSettings,
 ) -> Result<(Sender<ToFfmpegMessage>, Receiver<FromFfmpegMessage>), FfmpegError> {
-    let mut decoder_process = spawn_decoder(ffmpeg_path, input_video, render_settings.encoder.hardware)?;
+    let background_color = render_settings.background_color();
+    let (frame_source, background_color) = match (input_video, background_color) {
+        (Some(input_video), None) => {
+            let mut decoder_process = spawn_decoder(
+                ffmpeg_path,
+                input_video,
+                render_settings.encoder.hardware,
+                render_settings.time_range,
+            )?;
+            let decoder_iter = decoder_process
+                .iter()
+                .expect("Failed to create `FfmpegIterator` for decoder");
+            let frame_source = FrameSource::Decoder {
+                decoder_iter,
+                decoder_process,
+            };
+            (frame_source, None)
+        }
+        (_, background_color) => {
+            let generated_frames = GeneratedFrames::new(video_info, render_settings.frames_to_render(video_info));
+            let background_color = background_color.unwrap_or(Rgba([0, 0, 0, 255]));
+            (FrameSource::Generated(generated_frames), Some(background_color))
+        }
+    };
 
     let mut encoder_process = spawn_encoder(
         ffmpeg_path,
@@ -44,19 +72,21 @@ pub fn start_video_render(
         output_video,
         render_settings.upscale,
         render_settings.pad_4_3_to_16_9,
+        input_video,
+        render_settings.audio,
+        render_settings.time_range,
+        render_settings.output_format,
     )?;
 
     // Channels to communicate with ffmpeg handler thread
     let (from_ffmpeg_tx, from_ffmpeg_rx) = crossbeam_channel::unbounded();
     let (to_ffmpeg_tx, to_ffmpeg_rx) = crossbeam_channel::unbounded();
 
-    // Iterator over decoded video and OSD frames
+    // Iterator over video and OSD frames
     let frame_overlay_iter = FrameOverlayIter::new(
-        decoder_process
-            .iter()
-            .expect("Failed to create `FfmpegIterator` for decoder"),
-        decoder_process,
+        frame_source,
         osd_frames,
+        osd_grid_size,
         srt_frames,
         font_file,
         srt_font,
@@ -64,12 +94,12 @@ pub fn start_video_render(
         srt_options,
         from_ffmpeg_tx.clone(),
         to_ffmpeg_rx,
-        if render_settings.use_chroma_key {
-            Some(render_settings.chroma_key)
-        } else {
-            None
-        },
+        background_color,
         render_settings.pad_4_3_to_16_9,
+        render_settings
+            .time_range
+            .map(|r| r.start.as_secs_f32())
+            .unwrap_or_default(),
     );
 
     // On another thread run the decoder iterator to completion and feed the output to the encoder's stdin
@@ -110,12 +140,16 @@ pub fn spawn_decoder(
     ffmpeg_path: &PathBuf,
     input_video: &PathBuf,
     use_hwaccel: bool,
+    time_range: Option<TimeRange>,
 ) -> Result<FfmpegChild, FfmpegError> {
     let mut cmd = FfmpegCommand::new_with_path(ffmpeg_path);
     cmd.create_no_window();
     if use_hwaccel {
         cmd.args(["-hwaccel", "auto"]);
     }
+    if let Some(time_range) = time_range {
+        seek_input(&mut cmd, time_range);
+    }
     cmd.input(input_video.to_str().unwrap())
         .args(["-f", "rawvideo", "-pix_fmt", "rgba", "-"]);
     let decoder = cmd.spawn()?;
@@ -133,6 +167,10 @@ pub fn spawn_encoder(
     output_video: &PathBuf,
     upscale: UpscaleTarget,
     pad_4_3_to_16_9: bool,
+    audio_source: Option<&PathBuf>,
+    audio_mode: AudioMode,
+    time_range: Option<TimeRange>,
+    output_format: OutputFormat,
 ) -> Result<FfmpegChild, FfmpegError> {
     let mut encoder_command = FfmpegCommand::new_with_path(ffmpeg_path);
 
@@ -152,6 +190,32 @@ pub fn spawn_encoder(
         .rate(frame_rate)
         .input("-");
 
+    // The audio track is taken straight from the input video, next to the rendered frames coming in on stdin. The `?`
+    // makes the audio mapping optional so videos without an audio track still render.
+    let audio_codec = match (output_format, audio_mode) {
+        (_, AudioMode::Disabled) | (OutputFormat::PngSequence, _) => None,
+        // WebM only supports Opus and Vorbis audio so the audio from the input video can't be copied
+        (OutputFormat::Vp9Alpha, _) => Some("libopus"),
+        (_, AudioMode::Copy) => Some("copy"),
+        (_, AudioMode::Aac) => Some("aac"),
+    };
+    match (audio_source, audio_codec) {
+        (Some(audio_source), Some(audio_codec)) => {
+            if let Some(time_range) = time_range {
+                seek_input(&mut encoder_command, time_range);
+            }
+            encoder_command
+                .input(audio_source.to_str().unwrap())
+                .map("0:v:0")
+                .map("1:a:0?")
+                .codec_audio(audio_codec)
+                .arg("-shortest");
+        }
+        _ => {
+            encoder_command.no_audio();
+        }
+    }
+
     let mut filters = Vec::new();
 
     match upscale {
@@ -168,18 +232,43 @@ pub fn spawn_encoder(
         encoder_command.args(["-vf", &filters.join(",")]);
     }
 
-    encoder_command
-        .pix_fmt("yuv420p")
-        .codec_video(&video_encoder.name)
-        .args(["-b:v", &format!("{}M", bitrate_mbps)])
-        .args(&video_encoder.extra_args)
-        .overwrite()
-        .output(output_video.to_str().unwrap());
+    match output_format {
+        OutputFormat::Mp4 => encoder_command
+            .pix_fmt("yuv420p")
+            .codec_video(&video_encoder.name)
+            .args(["-b:v", &format!("{}M", bitrate_mbps)])
+            .args(&video_encoder.extra_args),
+        OutputFormat::ProRes4444 => encoder_command.pix_fmt("yuva444p10le").codec_video("prores_ks").args([
+            "-profile:v",
+            "4444",
+            "-vendor",
+            "apl0",
+        ]),
+        OutputFormat::Vp9Alpha => encoder_command
+            .pix_fmt("yuva420p")
+            .codec_video("libvpx-vp9")
+            .args(["-b:v", &format!("{}M", bitrate_mbps)])
+            .args(["-auto-alt-ref", "0"]),
+        OutputFormat::PngSequence => {
+            if let Some(output_dir) = output_video.parent() {
+                fs::create_dir_all(output_dir).map_err(FfmpegError::FailedToCreateOutputDirectory)?;
+            }
+            encoder_command.pix_fmt("rgba").codec_video("png")
+        }
+    };
+
+    encoder_command.overwrite().output(output_video.to_str().unwrap());
 
     let encoder = encoder_command.spawn()?;
     Ok(encoder)
 }
 
+/// Limit the next input of `cmd` to `time_range`. Must be called before adding the input.
+fn seek_input(cmd: &mut FfmpegCommand, time_range: TimeRange) {
+    cmd.seek(format!("{:.3}", time_range.start.as_secs_f64()))
+        .duration(format!("{:.3}", time_range.duration().as_secs_f64()));
+}
+
 fn manual_parse_progress(log_line: &str) -> Option<ffmpeg_sidecar::event::FfmpegProgress> {
     if !log_line.contains("frame=") || !log_line.contains("fps=") {
         return None;
@@ -1,6 +1,9 @@
+use std::time::Duration;
+
+use image::Rgba;
 use serde::{Deserialize, Serialize};
 
-use crate::ffmpeg::{Codec, Encoder};
+use crate::ffmpeg::{Codec, Encoder, VideoInfo};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum UpscaleTarget {
@@ -26,6 +29,90 @@ impl std::fmt::Display for UpscaleTarget {
     }
 }
 
+/// What to do with the audio track of the input video.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AudioMode {
+    #[serde(rename = "disabled")]
+    Disabled,
+    #[default]
+    #[serde(rename = "copy")]
+    Copy,
+    #[serde(rename = "aac")]
+    Aac,
+}
+
+impl std::fmt::Display for AudioMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                AudioMode::Disabled => "Disabled",
+                AudioMode::Copy => "Copy",
+                AudioMode::Aac => "AAC",
+            }
+        )
+    }
+}
+
+/// Container and codec of the rendered output. All formats except `Mp4` have an alpha channel and are rendered on a
+/// transparent background instead of the input video so the OSD can be composited in video editing software.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    #[serde(rename = "mp4")]
+    Mp4,
+    #[serde(rename = "prores4444")]
+    ProRes4444,
+    #[serde(rename = "vp9_alpha")]
+    Vp9Alpha,
+    #[serde(rename = "png_sequence")]
+    PngSequence,
+}
+
+impl OutputFormat {
+    pub fn is_transparent(&self) -> bool {
+        *self != OutputFormat::Mp4
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Mp4 => "mp4",
+            OutputFormat::ProRes4444 => "mov",
+            OutputFormat::Vp9Alpha => "webm",
+            OutputFormat::PngSequence => "png",
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OutputFormat::Mp4 => "MP4",
+                OutputFormat::ProRes4444 => "ProRes 4444 (.mov)",
+                OutputFormat::Vp9Alpha => "VP9 with alpha (.webm)",
+                OutputFormat::PngSequence => "PNG sequence",
+            }
+        )
+    }
+}
+
+/// Segment of the input video to render, relative to the start of the video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start: Duration,
+    pub end: Duration,
+}
+
+impl TimeRange {
+    pub fn duration(&self) -> Duration {
+        self.end.saturating_sub(self.start)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RenderSettings {
     pub encoder: Encoder,
@@ -36,6 +123,13 @@ pub struct RenderSettings {
     pub pad_4_3_to_16_9: bool,
     pub use_chroma_key: bool,
     pub chroma_key: [f32; 3],
+    #[serde(default)]
+    pub audio: AudioMode,
+    #[serde(default)]
+    pub output_format: OutputFormat,
+    // In and out points are specific to the loaded video so they are not saved with the rest of the settings
+    #[serde(skip)]
+    pub time_range: Option<TimeRange>,
 }
 
 impl Default for RenderSettings {
@@ -55,6 +149,31 @@ impl Default for RenderSettings {
             pad_4_3_to_16_9: false,
             use_chroma_key: false,
             chroma_key: [1.0 / 255.0, 177.0 / 255.0, 64.0 / 255.0],
+            audio: AudioMode::Copy,
+            output_format: OutputFormat::Mp4,
+            time_range: None,
+        }
+    }
+}
+
+impl RenderSettings {
+    /// Color the OSD is drawn on instead of the input video, if any.
+    pub fn background_color(&self) -> Option<Rgba<u8>> {
+        if self.output_format.is_transparent() {
+            Some(Rgba([0, 0, 0, 0]))
+        } else if self.use_chroma_key {
+            let [r, g, b] = self.chroma_key.map(|c| (c * 255.0) as u8);
+            Some(Rgba([r, g, b, 255]))
+        } else {
+            None
+        }
+    }
+
+    /// Number of frames the render will produce, used for progress reporting.
+    pub fn frames_to_render(&self, video_info: &VideoInfo) -> u32 {
+        match self.time_range {
+            Some(time_range) => (video_info.frame_rate * time_range.duration().as_secs_f32()) as u32,
+            None => video_info.total_frames,
         }
     }
 }
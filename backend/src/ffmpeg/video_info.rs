@@ -4,7 +4,7 @@ use ffprobe::FfProbe;
 
 use super::error::VideoInfoError;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VideoInfo {
     pub width: u32,
     pub height: u32,
@@ -20,6 +20,18 @@ impl VideoInfo {
         let info = ffprobe::ffprobe(file_path, ffprobe_path.to_path_buf())?;
         info.try_into()
     }
+
+    /// Properties of the frames to render when there is no input video, e.g. for an OSD-only overlay.
+    pub fn synthetic(width: u32, height: u32, frame_rate: f32, duration: Duration) -> Self {
+        Self {
+            width,
+            height,
+            frame_rate,
+            bitrate: 0,
+            duration,
+            total_frames: (frame_rate * duration.as_secs_f32()) as u32,
+        }
+    }
 }
 
 impl TryFrom<FfProbe> for VideoInfo {
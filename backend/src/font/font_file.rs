@@ -56,6 +56,11 @@ impl FontFile {
     pub fn get_character(&self, index: usize) -> Option<&RgbaImage> {
         self.characters.get(index)
     }
+
+    /// All characters of the font, indexed by glyph index.
+    pub fn characters(&self) -> &[RgbaImage] {
+        &self.characters
+    }
 }
 
 fn split_characters(
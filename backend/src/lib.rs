@@ -2,6 +2,7 @@
 #![allow(clippy::collapsible_else_if)]
 
 pub mod config;
+pub mod export;
 pub mod ffmpeg;
 pub mod font;
 pub mod osd;
@@ -0,0 +1,67 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Shared between the thread that runs an extraction and the UI, to follow the progress and cancel the extraction.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionHandle {
+    video_duration: Duration,
+    /// Progress as the bits of an `f32` between 0 and 1.
+    progress: Arc<AtomicU32>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ExtractionHandle {
+    /// Progress is reported relative to `video_duration`. With a zero duration the progress stays at 0.
+    pub fn new(video_duration: Duration) -> Self {
+        Self {
+            video_duration,
+            ..Default::default()
+        }
+    }
+
+    /// Fraction of the video that has been scanned.
+    pub fn progress(&self) -> f32 {
+        f32::from_bits(self.progress.load(Ordering::Relaxed))
+    }
+
+    /// Stop the extraction. The OSD frames found so far are returned.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn set_position(&self, pts_secs: f64) {
+        if self.video_duration.is_zero() {
+            return;
+        }
+        let progress = (pts_secs / self.video_duration.as_secs_f64()).clamp(0.0, 1.0) as f32;
+        self.progress.store(progress.to_bits(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_relative_to_video_duration() {
+        let handle = ExtractionHandle::new(Duration::from_secs(10));
+        let ui_handle = handle.clone();
+
+        handle.set_position(2.5);
+        assert_eq!(ui_handle.progress(), 0.25);
+        handle.set_position(12.0);
+        assert_eq!(ui_handle.progress(), 1.0);
+
+        ui_handle.cancel();
+        assert!(handle.is_cancelled());
+    }
+}
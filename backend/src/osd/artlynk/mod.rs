@@ -0,0 +1,323 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::{Duration, UNIX_EPOCH},
+};
+
+pub use self::handle::ExtractionHandle;
+use self::{
+    mp4::VideoTrack,
+    msp::{is_display_port_packet, DisplayPort},
+    sei::SeiReader,
+};
+use super::{
+    error::{Mp4Error, OsdFileError},
+    fc_firmware::FcFirmware,
+    osd_file::OsdFile,
+    source::{has_extension, OsdSource, SourceContext},
+};
+
+mod handle;
+mod mp4;
+mod msp;
+mod sei;
+
+/// Length of the start of the video that is scanned to detect OSD data.
+const DETECTION_DURATION: Duration = Duration::from_secs(2);
+/// Version of the SEI decoding, part of the cache file names so OSD data cached by an older version is extracted again.
+/// Increase it whenever changes to the decoding change the extracted frames.
+const DECODER_VERSION: u32 = 1;
+
+/// Extract SEI User Data entries from a video file, in presentation order. Stops early when the extraction is
+/// cancelled. Returns a list of (pts_seconds, user_data) tuples.
+fn extract_sei_data(
+    ffmpeg_path: &Path,
+    video_path: &Path,
+    max_duration: Option<Duration>,
+    handle: &ExtractionHandle,
+) -> Vec<(f64, Vec<u8>)> {
+    match read_sei_data(video_path, max_duration, handle) {
+        Ok(entries) => {
+            tracing::info!("Found {} SEI User Data entries in the video samples", entries.len());
+            entries
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Unable to read SEI data from the MP4 container ({}), falling back to ffmpeg",
+                e
+            );
+            extract_sei_data_ffmpeg(ffmpeg_path, video_path, max_duration, handle)
+        }
+    }
+}
+
+/// Read SEI User Data entries directly from the H.264/H.265 samples in the MP4 container, without decoding the video.
+fn read_sei_data(
+    video_path: &Path,
+    max_duration: Option<Duration>,
+    handle: &ExtractionHandle,
+) -> Result<Vec<(f64, Vec<u8>)>, Mp4Error> {
+    let track = VideoTrack::open(video_path)?;
+    let mut reader = SeiReader::open(video_path, &track)?;
+
+    let max_secs = max_duration.map(|d| d.as_secs_f64()).unwrap_or(f64::INFINITY);
+    let mut entries = Vec::new();
+    for sample in track.samples.iter().filter(|s| s.pts_secs < max_secs) {
+        if handle.is_cancelled() {
+            break;
+        }
+        for user_data in reader.user_data(sample)? {
+            entries.push((sample.pts_secs, user_data));
+        }
+        if max_duration.is_none() {
+            handle.set_position(sample.pts_secs);
+        }
+    }
+
+    // Samples are stored in decoding order
+    entries.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    Ok(entries)
+}
+
+/// Extract SEI User Data entries from a video file using ffmpeg showinfo filter. Much slower than reading the
+/// container directly because every frame is decoded, only used for files that can't be read as MP4.
+fn extract_sei_data_ffmpeg(
+    ffmpeg_path: &Path,
+    video_path: &Path,
+    max_duration: Option<Duration>,
+    handle: &ExtractionHandle,
+) -> Vec<(f64, Vec<u8>)> {
+    let mut command = Command::new(ffmpeg_path);
+
+    let duration_str = max_duration.map(|t| format!("{:.3}", t.as_secs_f64()));
+    if let Some(t) = &duration_str {
+        command.arg("-t").arg(t);
+    }
+
+    command.args([
+        "-i",
+        video_path.to_str().unwrap_or(""),
+        "-vf",
+        "showinfo",
+        "-f",
+        "null",
+        "-",
+    ]);
+    command.stdout(Stdio::null());
+    command.stderr(Stdio::piped());
+
+    #[cfg(target_os = "windows")]
+    std::os::windows::process::CommandExt::creation_flags(&mut command, crate::util::CREATE_NO_WINDOW);
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::error!("Failed to run ffmpeg showinfo: {}", e);
+            return Vec::new();
+        }
+    };
+
+    // showinfo logs a line with the timestamp of every frame, followed by its side data
+    let mut entries = Vec::new();
+    let mut pts = None;
+    let stderr = BufReader::new(child.stderr.take().expect("stderr is piped"));
+    for line in stderr.lines().map_while(Result::ok) {
+        if handle.is_cancelled() {
+            child.kill().ok();
+            break;
+        }
+
+        if let Some(pts_time) = line.split("pts_time:").nth(1) {
+            pts = pts_time.split_whitespace().next().and_then(|p| p.parse::<f64>().ok());
+            if let (Some(pts), None) = (pts, max_duration) {
+                handle.set_position(pts);
+            }
+        } else if let (Some(hex), Some(pts)) = (line.split("User Data=").nth(1), pts) {
+            tracing::debug!(
+                "Captured SEI hex (first 50 chars): {}",
+                &hex.chars().take(50).collect::<String>()
+            );
+            if let Some(user_data) = decode_showinfo_hex(hex) {
+                entries.push((pts, user_data));
+            }
+        }
+    }
+    child.wait().ok();
+
+    tracing::info!("Found {} SEI User Data entries in stderr", entries.len());
+    entries
+}
+
+/// Decode the hex dump of the user data printed by the showinfo filter.
+fn decode_showinfo_hex(hex_string: &str) -> Option<Vec<u8>> {
+    // 1. Clean the string: remove address prefixes like "00000010:" and colons
+    // showinfo often formats SEI data with address prefixes.
+    let mut cleaned = String::with_capacity(hex_string.len());
+    for line in hex_string.lines() {
+        let line = line.trim();
+        // If line starts with an address like "00000000: ", skip the address part
+        if let Some(pos) = line.find(": ") {
+            cleaned.push_str(&line[pos + 2..]);
+        } else {
+            cleaned.push_str(line);
+        }
+    }
+
+    // 2. Remove all non-hex characters (except spaces which hex::decode handles poorly if not removed)
+    let clean_hex: String = cleaned.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+
+    // Convert to bytes
+    match hex::decode(&clean_hex) {
+        Ok(b) => Some(b),
+        Err(e) => {
+            tracing::debug!(
+                "Hex decode failed for string: {}... error: {}",
+                &clean_hex.chars().take(20).collect::<String>(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// OSD data embedded in the SEI data of BetaFPV Artlynk recordings.
+pub struct ArtlynkSource;
+
+impl OsdSource for ArtlynkSource {
+    fn name(&self) -> &'static str {
+        "Artlynk"
+    }
+
+    /// Only checks the extension, whether the video actually contains OSD data is checked by [`contains_osd`] when it's
+    /// opened.
+    fn probe(&self, path: &Path) -> bool {
+        has_extension(path, &["mp4", "mov"])
+    }
+
+    fn open(&self, path: &Path, context: &SourceContext) -> Result<OsdFile, OsdFileError> {
+        let osd_file = match &context.cache_dir {
+            Some(cache_dir) => extract_osd_from_video_cached(&context.ffmpeg_path, path, cache_dir, &context.handle)?,
+            None => extract_osd_from_video(&context.ffmpeg_path, path, &context.handle)?,
+        };
+        osd_file.ok_or_else(|| OsdFileError::NoOsdData {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+/// Check whether the video carries Artlynk OSD data by looking for MSP DisplayPort packets in the SEI data of the first
+/// seconds of the video.
+pub fn contains_osd(ffmpeg_path: &Path, video_path: &Path, handle: &ExtractionHandle) -> bool {
+    extract_sei_data(ffmpeg_path, video_path, Some(DETECTION_DURATION), handle)
+        .iter()
+        .any(|(_, user_data)| is_display_port_packet(user_data))
+}
+
+/// Extract the OSD from the SEI data of an Artlynk recording. When the extraction is cancelled through `handle` the
+/// frames found until then are returned.
+#[tracing::instrument(ret, err, skip(handle))]
+pub fn extract_osd_from_video(
+    ffmpeg_path: &Path,
+    video_path: &Path,
+    handle: &ExtractionHandle,
+) -> Result<Option<OsdFile>, OsdFileError> {
+    if !contains_osd(ffmpeg_path, video_path, handle) {
+        tracing::info!("No Artlynk OSD data found in the first seconds of {:?}", video_path);
+        return Ok(None);
+    }
+
+    // Full scan: if SEI data was found, extract everything
+    let entries = extract_sei_data(ffmpeg_path, video_path, None, handle);
+    if handle.is_cancelled() {
+        tracing::info!(
+            "Artlynk OSD extraction cancelled, keeping {} SEI entries",
+            entries.len()
+        );
+    }
+    if entries.is_empty() {
+        tracing::info!("No SEI User Data found in video during full scan");
+        return Ok(None);
+    }
+
+    let mut display_port = DisplayPort::default();
+    // Other SEI user data, like the encoder settings that x264 writes, is skipped
+    let frames = entries
+        .iter()
+        .filter(|(_, user_data)| is_display_port_packet(user_data))
+        .filter_map(|(pts, user_data)| display_port.process(user_data, (*pts * 1000.0) as u32))
+        .collect::<Vec<_>>();
+
+    if frames.iter().all(|frame| frame.glyphs.is_empty()) {
+        tracing::info!("SEI data found but no valid OSD frames parsed");
+        return Ok(None);
+    }
+
+    tracing::info!("Extracted {} OSD frames from Artlynk SEI data", frames.len());
+
+    // Artlynk doesn't record the flight controller firmware, Betaflight fonts work for most of them
+    Ok(Some(OsdFile::from_frames(
+        video_path.to_path_buf(),
+        ArtlynkSource.name(),
+        FcFirmware::Betaflight,
+        display_port.grid_size(),
+        frames,
+    )))
+}
+
+/// Directory where extracted Artlynk OSD data is cached.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("rs", "", "walksnail-osd-tool").map(|dirs| dirs.cache_dir().join("artlynk"))
+}
+
+/// Same as [`extract_osd_from_video`] but the result is stored as an `.osd` file in `cache_dir` and reused on later
+/// calls, so long videos only have to be scanned once. Cached files are keyed by the size and modification time of the
+/// video and the decoder version. Partial results of a cancelled extraction aren't cached.
+#[tracing::instrument(ret, err, skip(handle))]
+pub fn extract_osd_from_video_cached(
+    ffmpeg_path: &Path,
+    video_path: &Path,
+    cache_dir: &Path,
+    handle: &ExtractionHandle,
+) -> Result<Option<OsdFile>, OsdFileError> {
+    let Some(cache_path) = cache_file_path(cache_dir, video_path) else {
+        return extract_osd_from_video(ffmpeg_path, video_path, handle);
+    };
+
+    if cache_path.exists() {
+        match OsdFile::open(cache_path.clone()) {
+            Ok(mut osd_file) => {
+                tracing::info!("Loaded cached Artlynk OSD data from {:?}", cache_path);
+                osd_file.file_path = video_path.to_path_buf();
+                osd_file.format = ArtlynkSource.name();
+                return Ok(Some(osd_file));
+            }
+            Err(e) => tracing::warn!("Ignoring invalid Artlynk OSD cache {:?}: {}", cache_path, e),
+        }
+    }
+
+    let osd_file = extract_osd_from_video(ffmpeg_path, video_path, handle)?;
+    if let (Some(osd_file), false) = (&osd_file, handle.is_cancelled()) {
+        if let Err(e) = fs::create_dir_all(cache_dir)
+            .map_err(OsdFileError::UnableToWriteFile)
+            .and_then(|_| osd_file.write(&cache_path))
+        {
+            tracing::warn!("Failed to cache Artlynk OSD data: {}", e);
+        }
+    }
+    Ok(osd_file)
+}
+
+fn cache_file_path(cache_dir: &Path, video_path: &Path) -> Option<PathBuf> {
+    let metadata = fs::metadata(video_path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    let stem = video_path.file_stem()?.to_string_lossy();
+    Some(cache_dir.join(format!(
+        "{}_{}_{}_v{}.osd",
+        stem,
+        metadata.len(),
+        modified.as_secs(),
+        DECODER_VERSION
+    )))
+}
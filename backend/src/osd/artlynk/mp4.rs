@@ -0,0 +1,435 @@
+//! Minimal MP4 (ISO BMFF) reader that finds the samples of the H.264/H.265 video track without decoding anything.
+
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::osd::error::Mp4Error;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Codec {
+    H264,
+    H265,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// Position of the sample in the file.
+    pub offset: u64,
+    pub size: u32,
+    /// Presentation time relative to the first frame.
+    pub pts_secs: f64,
+}
+
+#[derive(Debug)]
+pub struct VideoTrack {
+    pub codec: Codec,
+    /// Number of bytes of the length prefix in front of every NAL unit.
+    pub nal_length_size: usize,
+    /// Samples in decoding order.
+    pub samples: Vec<Sample>,
+}
+
+impl VideoTrack {
+    pub fn open(path: &Path) -> Result<Self, Mp4Error> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let moov = read_top_level_box(&mut BufReader::new(file), "moov")?;
+        parse_moov(&moov, file_len)
+    }
+}
+
+/// Read the body of the first top level box of the given type. Other boxes, like the large `mdat`, are skipped.
+fn read_top_level_box<R: Read + Seek>(reader: &mut R, box_type: &'static str) -> Result<Vec<u8>, Mp4Error> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let mut position = reader.seek(SeekFrom::Start(0))?;
+
+    while position + 8 <= file_len {
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        let mut size = u32::from_be_bytes(header[..4].try_into().unwrap()) as u64;
+        let mut header_len = 8;
+        if size == 1 {
+            let mut large_size = [0u8; 8];
+            reader.read_exact(&mut large_size)?;
+            size = u64::from_be_bytes(large_size);
+            header_len = 16;
+        } else if size == 0 {
+            size = file_len - position;
+        }
+        if size < header_len || size > file_len - position {
+            return Err(Mp4Error::InvalidBox("top level"));
+        }
+
+        if &header[4..] == box_type.as_bytes() {
+            let mut body = vec![0u8; (size - header_len) as usize];
+            reader.read_exact(&mut body)?;
+            return Ok(body);
+        }
+
+        position += size;
+        reader.seek(SeekFrom::Start(position))?;
+    }
+
+    Err(Mp4Error::MissingBox(box_type))
+}
+
+/// Iterator over the child boxes in the body of a box, as `(type, body)`.
+struct Boxes<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for Boxes<'a> {
+    type Item = ([u8; 4], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.len() < 8 {
+            return None;
+        }
+        let mut size = read_u32(self.data, 0)? as usize;
+        let box_type: [u8; 4] = self.data[4..8].try_into().unwrap();
+        let mut header_len = 8;
+        if size == 1 {
+            size = read_u64(self.data, 8)? as usize;
+            header_len = 16;
+        } else if size == 0 {
+            size = self.data.len();
+        }
+        if size < header_len || size > self.data.len() {
+            self.data = &[];
+            return None;
+        }
+
+        let body = &self.data[header_len..size];
+        self.data = &self.data[size..];
+        Some((box_type, body))
+    }
+}
+
+fn boxes(data: &[u8]) -> Boxes<'_> {
+    Boxes { data }
+}
+
+fn find_box<'a>(data: &'a [u8], box_type: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(data).find(|(t, _)| t == box_type).map(|(_, body)| body)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().unwrap()))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(offset..offset + 8)?.try_into().unwrap()))
+}
+
+/// Entries of a full box table, every entry is `entry_len` bytes after the version, flags and entry count.
+fn table<'a>(data: &'a [u8], entry_len: usize, name: &'static str) -> Result<Vec<&'a [u8]>, Mp4Error> {
+    let count = read_u32(data, 4).ok_or(Mp4Error::InvalidBox(name))? as usize;
+    let entries = count
+        .checked_mul(entry_len)
+        .and_then(|len| data.get(8..)?.get(..len))
+        .ok_or(Mp4Error::InvalidBox(name))?
+        .chunks(entry_len)
+        .collect();
+    Ok(entries)
+}
+
+/// `file_len` bounds the sample tables, every sample has to be stored in the file.
+fn parse_moov(moov: &[u8], file_len: u64) -> Result<VideoTrack, Mp4Error> {
+    boxes(moov)
+        .filter(|(t, _)| t == b"trak")
+        .find_map(|(_, trak)| parse_trak(trak, file_len).transpose())
+        .unwrap_or(Err(Mp4Error::NoVideoTrack))
+}
+
+/// Returns `None` for tracks that aren't H.264 or H.265 video.
+fn parse_trak(trak: &[u8], file_len: u64) -> Result<Option<VideoTrack>, Mp4Error> {
+    let mdia = find_box(trak, b"mdia").ok_or(Mp4Error::MissingBox("mdia"))?;
+    let hdlr = find_box(mdia, b"hdlr").ok_or(Mp4Error::MissingBox("hdlr"))?;
+    if hdlr.get(8..12) != Some(&b"vide"[..]) {
+        return Ok(None);
+    }
+
+    let mdhd = find_box(mdia, b"mdhd").ok_or(Mp4Error::MissingBox("mdhd"))?;
+    let timescale = match mdhd.first() {
+        Some(1) => read_u32(mdhd, 20),
+        _ => read_u32(mdhd, 12),
+    }
+    .filter(|t| *t > 0)
+    .ok_or(Mp4Error::InvalidBox("mdhd"))?;
+
+    let stbl = find_box(mdia, b"minf")
+        .and_then(|minf| find_box(minf, b"stbl"))
+        .ok_or(Mp4Error::MissingBox("stbl"))?;
+
+    let stsd = find_box(stbl, b"stsd").ok_or(Mp4Error::MissingBox("stsd"))?;
+    let Some((codec, nal_length_size)) = parse_stsd(stsd)? else {
+        return Ok(None);
+    };
+
+    let sizes = parse_stsz(find_box(stbl, b"stsz").ok_or(Mp4Error::MissingBox("stsz"))?, file_len)?;
+    let offsets = sample_offsets(stbl, &sizes)?;
+    let pts = sample_pts(stbl, sizes.len(), timescale)?;
+
+    let samples = sizes
+        .into_iter()
+        .zip(offsets)
+        .zip(pts)
+        .map(|((size, offset), pts_secs)| Sample { offset, size, pts_secs })
+        .collect();
+
+    Ok(Some(VideoTrack {
+        codec,
+        nal_length_size,
+        samples,
+    }))
+}
+
+fn parse_stsd(stsd: &[u8]) -> Result<Option<(Codec, usize)>, Mp4Error> {
+    // Version, flags and entry count, followed by the first sample entry
+    let (entry_type, entry) = boxes(stsd.get(8..).ok_or(Mp4Error::InvalidBox("stsd"))?)
+        .next()
+        .ok_or(Mp4Error::InvalidBox("stsd"))?;
+
+    // Visual sample entries have 78 bytes of fixed fields before the child boxes
+    const VISUAL_SAMPLE_ENTRY_BYTES: usize = 78;
+    let children = entry.get(VISUAL_SAMPLE_ENTRY_BYTES..).unwrap_or_default();
+
+    let (codec, nal_length_size) = match &entry_type {
+        b"avc1" | b"avc3" => {
+            let avcc = find_box(children, b"avcC").ok_or(Mp4Error::MissingBox("avcC"))?;
+            (Codec::H264, avcc.get(4).ok_or(Mp4Error::InvalidBox("avcC"))?)
+        }
+        b"hvc1" | b"hev1" => {
+            let hvcc = find_box(children, b"hvcC").ok_or(Mp4Error::MissingBox("hvcC"))?;
+            (Codec::H265, hvcc.get(21).ok_or(Mp4Error::InvalidBox("hvcC"))?)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some((codec, (nal_length_size & 0x03) as usize + 1)))
+}
+
+fn parse_stsz(stsz: &[u8], file_len: u64) -> Result<Vec<u32>, Mp4Error> {
+    let sample_size = read_u32(stsz, 4).ok_or(Mp4Error::InvalidBox("stsz"))?;
+    let count = read_u32(stsz, 8).ok_or(Mp4Error::InvalidBox("stsz"))?;
+    if sample_size != 0 {
+        if count as u64 * sample_size as u64 > file_len {
+            return Err(Mp4Error::InvalidBox("stsz"));
+        }
+        return Ok(vec![sample_size; count as usize]);
+    }
+
+    let sizes = stsz
+        .get(12..)
+        .unwrap_or_default()
+        .chunks_exact(4)
+        .take(count as usize)
+        .map(|size| u32::from_be_bytes(size.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    if sizes.len() != count as usize {
+        return Err(Mp4Error::InvalidBox("stsz"));
+    }
+    Ok(sizes)
+}
+
+fn sample_offsets(stbl: &[u8], sizes: &[u32]) -> Result<Vec<u64>, Mp4Error> {
+    let chunk_offsets: Vec<u64> = if let Some(stco) = find_box(stbl, b"stco") {
+        table(stco, 4, "stco")?
+            .into_iter()
+            .map(|e| read_u32(e, 0).unwrap() as u64)
+            .collect()
+    } else if let Some(co64) = find_box(stbl, b"co64") {
+        table(co64, 8, "co64")?
+            .into_iter()
+            .map(|e| read_u64(e, 0).unwrap())
+            .collect()
+    } else {
+        return Err(Mp4Error::MissingBox("stco"));
+    };
+
+    // (first chunk, samples per chunk), chunks are numbered from 1
+    let sample_to_chunk = table(find_box(stbl, b"stsc").ok_or(Mp4Error::MissingBox("stsc"))?, 12, "stsc")?
+        .into_iter()
+        .map(|e| (read_u32(e, 0).unwrap() as usize, read_u32(e, 4).unwrap() as usize))
+        .collect::<Vec<_>>();
+
+    let sample_count = sizes.len();
+    let mut offsets = Vec::with_capacity(sample_count);
+    let mut sizes = sizes.iter();
+    for (run, &(first_chunk, samples_per_chunk)) in sample_to_chunk.iter().enumerate() {
+        let last_chunk = match sample_to_chunk.get(run + 1) {
+            Some(&(next_first_chunk, _)) => next_first_chunk.checked_sub(1).ok_or(Mp4Error::InvalidBox("stsc"))?,
+            None => chunk_offsets.len(),
+        };
+        for chunk in first_chunk..=last_chunk {
+            let mut offset = *chunk
+                .checked_sub(1)
+                .and_then(|index| chunk_offsets.get(index))
+                .ok_or(Mp4Error::InvalidBox("stsc"))?;
+            for size in sizes.by_ref().take(samples_per_chunk) {
+                offsets.push(offset);
+                offset += *size as u64;
+            }
+        }
+    }
+
+    if offsets.len() != sample_count {
+        return Err(Mp4Error::InvalidBox("stsc"));
+    }
+    Ok(offsets)
+}
+
+fn sample_pts(stbl: &[u8], sample_count: usize, timescale: u32) -> Result<Vec<f64>, Mp4Error> {
+    let mut pts = Vec::with_capacity(sample_count);
+    let mut dts = 0i64;
+    for entry in table(find_box(stbl, b"stts").ok_or(Mp4Error::MissingBox("stts"))?, 8, "stts")? {
+        // Entries past the last sample are ignored
+        let count = (read_u32(entry, 0).unwrap() as usize).min(sample_count - pts.len());
+        let delta = read_u32(entry, 4).unwrap() as i64;
+        for _ in 0..count {
+            pts.push(dts);
+            dts = dts.checked_add(delta).ok_or(Mp4Error::InvalidBox("stts"))?;
+        }
+    }
+
+    // Composition offsets for reordered frames
+    if let Some(ctts) = find_box(stbl, b"ctts") {
+        let mut pts_iter = pts.iter_mut();
+        for entry in table(ctts, 8, "ctts")? {
+            let count = read_u32(entry, 0).unwrap() as usize;
+            let offset = read_u32(entry, 4).unwrap() as i32 as i64;
+            for pts in pts_iter.by_ref().take(count) {
+                *pts = pts.checked_add(offset).ok_or(Mp4Error::InvalidBox("ctts"))?;
+            }
+        }
+    }
+
+    let first_pts = pts.iter().copied().min().unwrap_or_default();
+    Ok(pts
+        .into_iter()
+        .map(|pts| (pts - first_pts) as f64 / timescale as f64)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mp4_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut data = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(box_type);
+        data.extend_from_slice(body);
+        data
+    }
+
+    fn full_box_table(box_type: &[u8; 4], entries: &[&[u32]]) -> Vec<u8> {
+        let mut body = vec![0u8; 4];
+        body.extend((entries.len() as u32).to_be_bytes());
+        for entry in entries {
+            for value in *entry {
+                body.extend(value.to_be_bytes());
+            }
+        }
+        mp4_box(box_type, &body)
+    }
+
+    #[test]
+    fn samples_of_h264_track() {
+        let mut avc1 = vec![0u8; 78];
+        avc1.extend(mp4_box(b"avcC", &[1, 100, 0, 40, 0xff]));
+        let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        stsd.extend(mp4_box(b"avc1", &avc1));
+
+        let mut stsz = vec![0u8; 4];
+        stsz.extend(0u32.to_be_bytes());
+        stsz.extend(3u32.to_be_bytes());
+        for size in [100u32, 20, 30] {
+            stsz.extend(size.to_be_bytes());
+        }
+
+        let stbl = [
+            mp4_box(b"stsd", &stsd),
+            full_box_table(b"stts", &[&[3, 1000]]),
+            full_box_table(b"ctts", &[&[1, 1000], &[1, 2000], &[1, 0]]),
+            mp4_box(b"stsz", &stsz),
+            full_box_table(b"stsc", &[&[1, 2, 1], &[2, 1, 1]]),
+            full_box_table(b"stco", &[&[500], &[2000]]),
+        ]
+        .concat();
+
+        let mut mdhd = vec![0u8; 12];
+        mdhd.extend(30_000u32.to_be_bytes());
+        mdhd.extend([0u8; 8]);
+        let mut hdlr = vec![0u8; 8];
+        hdlr.extend(b"vide");
+        hdlr.extend([0u8; 12]);
+
+        let minf = mp4_box(b"minf", &mp4_box(b"stbl", &stbl));
+        let mdia = mp4_box(
+            b"mdia",
+            &[mp4_box(b"mdhd", &mdhd), mp4_box(b"hdlr", &hdlr), minf].concat(),
+        );
+        let moov = mp4_box(b"trak", &mdia);
+
+        let track = parse_moov(&moov, 3000).unwrap();
+        assert_eq!(track.codec, Codec::H264);
+        assert_eq!(track.nal_length_size, 4);
+
+        let offsets = track.samples.iter().map(|s| s.offset).collect::<Vec<_>>();
+        assert_eq!(offsets, [500, 600, 2000]);
+
+        let pts = track.samples.iter().map(|s| s.pts_secs).collect::<Vec<_>>();
+        assert_eq!(pts, [0.0, 2.0 / 30.0, 1.0 / 30.0]);
+    }
+
+    #[test]
+    fn reject_box_larger_than_file() {
+        let mut data = mp4_box(b"moov", &[0u8; 16]);
+        data[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        let result = read_top_level_box(&mut std::io::Cursor::new(data), "moov");
+        assert!(matches!(result, Err(Mp4Error::InvalidBox(_))));
+    }
+
+    #[test]
+    fn reject_chunk_numbered_zero() {
+        let stbl = [
+            full_box_table(b"stsc", &[&[1, 1, 1], &[0, 1, 1]]),
+            full_box_table(b"stco", &[&[500]]),
+        ]
+        .concat();
+        assert!(matches!(
+            sample_offsets(&stbl, &[100, 100]),
+            Err(Mp4Error::InvalidBox("stsc"))
+        ));
+    }
+
+    #[test]
+    fn reject_more_samples_than_fit_in_file() {
+        let mut stsz = vec![0u8; 4];
+        stsz.extend(100u32.to_be_bytes());
+        stsz.extend(u32::MAX.to_be_bytes());
+        assert!(matches!(parse_stsz(&stsz, 1000), Err(Mp4Error::InvalidBox("stsz"))));
+
+        let mut stsz = vec![0u8; 4];
+        stsz.extend(0u32.to_be_bytes());
+        stsz.extend(u32::MAX.to_be_bytes());
+        stsz.extend(100u32.to_be_bytes());
+        assert!(matches!(parse_stsz(&stsz, 1000), Err(Mp4Error::InvalidBox("stsz"))));
+    }
+
+    #[test]
+    fn stop_timestamps_at_sample_count() {
+        let stbl = full_box_table(b"stts", &[&[u32::MAX, 1000], &[u32::MAX, u32::MAX]]);
+        assert_eq!(sample_pts(&stbl, 2, 1000).unwrap(), [0.0, 1.0]);
+    }
+
+    #[test]
+    fn skip_audio_tracks() {
+        let mut hdlr = vec![0u8; 8];
+        hdlr.extend(b"soun");
+        let moov = mp4_box(b"trak", &mp4_box(b"mdia", &mp4_box(b"hdlr", &hdlr)));
+        assert!(matches!(parse_moov(&moov, 1000), Err(Mp4Error::NoVideoTrack)));
+    }
+}
@@ -0,0 +1,336 @@
+//! Decoder for the MSP DisplayPort commands that Artlynk embeds in the SEI data of its recordings.
+
+use crate::osd::{frame::Frame, glyph::GridSize};
+
+/// Offset of the first command in a packet, after the command count and the packet header.
+const PACKET_HEADER_BYTES: usize = 9;
+/// Size and MSP command id in front of every command payload.
+const COMMAND_HEADER_BYTES: usize = 2;
+/// MSP command id of the DisplayPort commands.
+const MSP_DISPLAYPORT: u8 = 182;
+/// The low bits of the attribute byte select the font page, which are the high bits of the glyph index.
+const ATTRIBUTE_FONT_PAGE_MASK: u8 = 0x03;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Heartbeat,
+    Release,
+    ClearScreen,
+    WriteString {
+        row: u8,
+        col: u8,
+        attribute: u8,
+        text: Vec<u8>,
+    },
+    DrawScreen,
+    Other(u8),
+}
+
+impl Command {
+    fn parse(payload: &[u8]) -> Option<Self> {
+        let (&subcommand, args) = payload.split_first()?;
+        let command = match subcommand {
+            0 => Command::Heartbeat,
+            1 => Command::Release,
+            2 => Command::ClearScreen,
+            3 => Self::parse_write_string(args)?,
+            4 => Command::DrawScreen,
+            other => Command::Other(other),
+        };
+        Some(command)
+    }
+
+    fn parse_write_string(args: &[u8]) -> Option<Self> {
+        let (&[row, col, attribute], text) = args.split_first_chunk::<3>()?;
+        Some(Command::WriteString {
+            row,
+            col,
+            attribute,
+            text: text.to_vec(),
+        })
+    }
+}
+
+/// Split the SEI user data into MSP commands and keep the DisplayPort ones. Flight controllers send other MSP commands
+/// in the same packets, those are skipped.
+///
+/// Packets without any DisplayPort command id are decoded like the first Artlynk recordings were: every command is a
+/// string write and every packet holds the whole OSD. Nothing else tells those packets apart from other user data, so
+/// they're only accepted when all of their commands fit in the packet.
+fn parse_packet(user_data: &[u8]) -> Option<Vec<Command>> {
+    // Structural removal: Remove every 3rd byte (the padding byte)
+    // Artlynk SEI format often packs 2 bytes of data and 1 byte of filler (0xff)
+    let data = user_data
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| (i + 1) % 3 != 0)
+        .map(|(_, &b)| b)
+        .collect::<Vec<_>>();
+
+    if data.len() < PACKET_HEADER_BYTES {
+        return None;
+    }
+
+    let num_commands = data[0] as usize;
+    let mut offset = PACKET_HEADER_BYTES;
+    let mut msp_commands = Vec::with_capacity(num_commands);
+    for _ in 0..num_commands {
+        // Payload size, MSP command id, then the payload starting with the DisplayPort subcommand. Commands cut off at
+        // the end of the packet are dropped.
+        let (Some(&payload_len), Some(&msp_command)) = (data.get(offset), data.get(offset + 1)) else {
+            break;
+        };
+        let start = offset + COMMAND_HEADER_BYTES;
+        let Some(payload) = data.get(start..start + payload_len as usize) else {
+            break;
+        };
+        msp_commands.push((msp_command, payload));
+        offset = start + payload_len as usize;
+    }
+
+    if msp_commands
+        .iter()
+        .any(|&(msp_command, _)| msp_command == MSP_DISPLAYPORT)
+    {
+        let commands = msp_commands
+            .into_iter()
+            .filter(|&(msp_command, _)| msp_command == MSP_DISPLAYPORT)
+            .filter_map(|(_, payload)| Command::parse(payload))
+            .collect();
+        return Some(commands);
+    }
+
+    if msp_commands.len() < num_commands {
+        return None;
+    }
+    // The subcommand byte is skipped without looking at it
+    let writes = msp_commands
+        .into_iter()
+        .filter_map(|(_, payload)| Command::parse_write_string(payload.get(1..)?))
+        .collect::<Vec<_>>();
+    if writes.is_empty() {
+        return Some(writes);
+    }
+    Some(std::iter::once(Command::ClearScreen).chain(writes).collect())
+}
+
+/// Whether the SEI user data is a packet with MSP DisplayPort commands, which is how Artlynk embeds the OSD.
+pub fn is_display_port_packet(user_data: &[u8]) -> bool {
+    parse_packet(user_data).is_some_and(|commands| commands.iter().any(|command| !matches!(command, Command::Other(_))))
+}
+
+/// Keeps the state of the OSD canvas between packets, like the goggles do. Frames are produced when the flight
+/// controller commits the canvas with a draw screen command.
+pub struct DisplayPort {
+    grid_size: GridSize,
+    canvas: Vec<u16>,
+    /// Streams that never send draw screen commands are drawn after every packet instead.
+    draw_commands_seen: bool,
+}
+
+impl Default for DisplayPort {
+    fn default() -> Self {
+        Self::new(GridSize::HD)
+    }
+}
+
+impl DisplayPort {
+    pub fn new(grid_size: GridSize) -> Self {
+        Self {
+            grid_size,
+            canvas: vec![0; (grid_size.width * grid_size.height) as usize],
+            draw_commands_seen: false,
+        }
+    }
+
+    pub fn grid_size(&self) -> GridSize {
+        self.grid_size
+    }
+
+    /// Apply the commands in the SEI user data of one video frame. Returns the frame that was drawn, if any.
+    pub fn process(&mut self, user_data: &[u8], time_millis: u32) -> Option<Frame> {
+        let commands = parse_packet(user_data)?;
+
+        let mut drawn = false;
+        let mut changed = false;
+        for command in commands {
+            match command {
+                Command::ClearScreen => {
+                    self.canvas.fill(0);
+                    changed = true;
+                }
+                Command::WriteString {
+                    row,
+                    col,
+                    attribute,
+                    text,
+                } => {
+                    self.write_string(row, col, attribute, &text);
+                    changed = true;
+                }
+                Command::DrawScreen => {
+                    self.draw_commands_seen = true;
+                    drawn = true;
+                }
+                Command::Heartbeat | Command::Release | Command::Other(_) => {}
+            }
+        }
+
+        if drawn || (changed && !self.draw_commands_seen) {
+            Some(self.frame(time_millis))
+        } else {
+            None
+        }
+    }
+
+    fn write_string(&mut self, row: u8, col: u8, attribute: u8, text: &[u8]) {
+        let (width, height) = (self.grid_size.width as usize, self.grid_size.height as usize);
+        let row = row as usize;
+        if row >= height {
+            return;
+        }
+
+        let font_page = ((attribute & ATTRIBUTE_FONT_PAGE_MASK) as u16) << 8;
+        for (i, &glyph_byte) in text.iter().enumerate() {
+            let col = col as usize + i;
+            if col >= width {
+                break;
+            }
+            self.canvas[row * width + col] = font_page | glyph_byte as u16;
+        }
+    }
+
+    fn frame(&self, time_millis: u32) -> Frame {
+        Frame::from_grid(time_millis, self.canvas.iter().copied(), self.grid_size.width as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_string(row: u8, col: u8, attribute: u8, text: &[u8]) -> Vec<u8> {
+        let mut command = vec![text.len() as u8 + 4, MSP_DISPLAYPORT, 3, row, col, attribute];
+        command.extend_from_slice(text);
+        command
+    }
+
+    fn subcommand(subcommand: u8) -> Vec<u8> {
+        vec![1, MSP_DISPLAYPORT, subcommand]
+    }
+
+    /// Build the SEI user data for a packet, including the filler bytes.
+    fn packet(commands: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = vec![commands.len() as u8];
+        data.resize(PACKET_HEADER_BYTES, 0);
+        data.extend(commands.concat());
+        data.chunks(2).flat_map(|pair| [pair, &[0xff]].concat()).collect()
+    }
+
+    fn glyphs(frame: &Frame) -> Vec<(u32, u32, u16)> {
+        frame
+            .glyphs
+            .iter()
+            .map(|g| (g.grid_position.x, g.grid_position.y, g.index))
+            .collect()
+    }
+
+    #[test]
+    fn keep_canvas_between_packets() {
+        let mut display_port = DisplayPort::default();
+        display_port.process(&packet(&[subcommand(4)]), 0);
+        assert!(display_port
+            .process(&packet(&[write_string(1, 2, 0, b"AB")]), 50)
+            .is_none());
+
+        let frame = display_port
+            .process(&packet(&[write_string(3, 0, 1, &[0x10]), subcommand(4)]), 100)
+            .unwrap();
+        assert_eq!(frame.time_millis, 100);
+        assert_eq!(glyphs(&frame), [(2, 1, 0x41), (3, 1, 0x42), (0, 3, 0x110)]);
+    }
+
+    #[test]
+    fn clear_screen() {
+        let mut display_port = DisplayPort::default();
+        display_port.process(&packet(&[write_string(0, 0, 0, b"A"), subcommand(4)]), 0);
+
+        let frame = display_port
+            .process(&packet(&[subcommand(2), subcommand(4)]), 100)
+            .unwrap();
+        assert!(frame.glyphs.is_empty());
+    }
+
+    #[test]
+    fn draw_every_packet_without_draw_commands() {
+        let mut display_port = DisplayPort::default();
+        let frame = display_port
+            .process(&packet(&[write_string(0, 0, 0, b"A")]), 0)
+            .unwrap();
+        assert_eq!(glyphs(&frame), [(0, 0, 0x41)]);
+        assert!(display_port.process(&packet(&[subcommand(0)]), 100).is_none());
+    }
+
+    #[test]
+    fn ignore_writes_outside_of_canvas() {
+        let mut display_port = DisplayPort::default();
+        let frame = display_port
+            .process(
+                &packet(&[
+                    write_string(GridSize::HD.height as u8, 0, 0, b"A"),
+                    write_string(0, 52, 0, b"BC"),
+                ]),
+                0,
+            )
+            .unwrap();
+        assert_eq!(glyphs(&frame), [(52, 0, 0x42)]);
+    }
+
+    #[test]
+    fn skip_other_msp_commands() {
+        let mut display_port = DisplayPort::default();
+        // A status command whose payload starts like a write string command
+        let status = vec![5, 101, 3, 0, 0, 0, b'X'];
+        let frame = display_port
+            .process(&packet(&[status, write_string(0, 1, 0, b"A"), subcommand(4)]), 0)
+            .unwrap();
+        assert_eq!(glyphs(&frame), [(1, 0, 0x41)]);
+    }
+
+    #[test]
+    fn decode_packets_without_display_port_ids_as_writes() {
+        let mut display_port = DisplayPort::default();
+        let first = vec![6, 0, 0, 1, 2, 0, b'A', b'B'];
+        let frame = display_port.process(&packet(&[first]), 0).unwrap();
+        assert_eq!(glyphs(&frame), [(2, 1, 0x41), (3, 1, 0x42)]);
+
+        // Every packet is a whole frame
+        let second = packet(&[vec![5, 0, 0, 3, 0, 1, 0x10]]);
+        assert!(is_display_port_packet(&second));
+        let frame = display_port.process(&second, 100).unwrap();
+        assert_eq!(glyphs(&frame), [(0, 3, 0x110)]);
+    }
+
+    #[test]
+    fn reject_truncated_packets_without_display_port_ids() {
+        let mut data = packet(&[vec![6, 0, 0, 1, 2, 0, b'A', b'B']]);
+        data.truncate(data.len() - 3);
+        assert!(!is_display_port_packet(&data));
+
+        let mut encoder_settings = b"x264 - core 164 - H.264/MPEG-4 AVC codec".to_vec();
+        encoder_settings.resize(60, b' ');
+        assert!(!is_display_port_packet(&encoder_settings));
+    }
+
+    #[test]
+    fn recognize_display_port_packets() {
+        assert!(is_display_port_packet(&packet(&[
+            write_string(0, 0, 0, b"A"),
+            subcommand(4)
+        ])));
+        assert!(is_display_port_packet(&packet(&[vec![1, 101, 4], subcommand(4)])));
+        assert!(!is_display_port_packet(&packet(&[])));
+        assert!(!is_display_port_packet(&packet(&[vec![1, 101, 4]])));
+        assert!(!is_display_port_packet(&[0x47, 0x41, 0x39, 0x34]));
+    }
+}
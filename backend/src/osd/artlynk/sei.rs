@@ -0,0 +1,162 @@
+//! Reads user data unregistered SEI messages (payload type 5) from the H.264/H.265 NAL units of MP4 samples.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use super::mp4::{Codec, Sample, VideoTrack};
+
+const USER_DATA_UNREGISTERED: u32 = 5;
+const UUID_BYTES: usize = 16;
+
+pub struct SeiReader {
+    reader: BufReader<File>,
+    codec: Codec,
+    nal_length_size: usize,
+}
+
+impl SeiReader {
+    pub fn open(path: &Path, track: &VideoTrack) -> io::Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path)?),
+            codec: track.codec,
+            nal_length_size: track.nal_length_size,
+        })
+    }
+
+    /// The user data of the user data unregistered SEI messages in a sample, without the UUID. Only SEI NAL units are
+    /// read, all other NAL units are skipped.
+    pub fn user_data(&mut self, sample: &Sample) -> io::Result<Vec<Vec<u8>>> {
+        self.reader.seek(SeekFrom::Start(sample.offset))?;
+
+        let header_len = match self.codec {
+            Codec::H264 => 1,
+            Codec::H265 => 2,
+        };
+        let mut user_data = Vec::new();
+        let mut remaining = sample.size as usize;
+        while remaining >= self.nal_length_size + header_len {
+            let mut length_bytes = [0u8; 4];
+            self.reader.read_exact(&mut length_bytes[4 - self.nal_length_size..])?;
+            let nal_len = u32::from_be_bytes(length_bytes) as usize;
+            remaining -= self.nal_length_size;
+            if nal_len < header_len || nal_len > remaining {
+                break;
+            }
+            remaining -= nal_len;
+
+            let mut header = [0u8; 2];
+            self.reader.read_exact(&mut header[..header_len])?;
+            if is_sei(self.codec, &header) {
+                let mut rbsp = vec![0u8; nal_len - header_len];
+                self.reader.read_exact(&mut rbsp)?;
+                user_data.extend(parse_sei_rbsp(&remove_emulation_prevention(&rbsp)));
+            } else {
+                self.reader.seek_relative((nal_len - header_len) as i64)?;
+            }
+        }
+        Ok(user_data)
+    }
+}
+
+fn is_sei(codec: Codec, header: &[u8; 2]) -> bool {
+    match codec {
+        Codec::H264 => header[0] & 0x1f == 6,
+        // Prefix and suffix SEI
+        Codec::H265 => matches!((header[0] >> 1) & 0x3f, 39 | 40),
+    }
+}
+
+/// Remove the `0x03` bytes that the encoder inserts after two zero bytes.
+fn remove_emulation_prevention(data: &[u8]) -> Vec<u8> {
+    let mut rbsp = Vec::with_capacity(data.len());
+    let mut zeros = 0;
+    for &byte in data {
+        if zeros >= 2 && byte == 0x03 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if byte == 0 { zeros + 1 } else { 0 };
+        rbsp.push(byte);
+    }
+    rbsp
+}
+
+/// Read a payload type or size, which is coded as a number of `0xff` bytes followed by the remainder.
+fn read_sei_value(data: &[u8], position: &mut usize) -> Option<u32> {
+    let mut value = 0u32;
+    loop {
+        let byte = *data.get(*position)?;
+        *position += 1;
+        value = value.checked_add(byte as u32)?;
+        if byte != 0xff {
+            return Some(value);
+        }
+    }
+}
+
+fn parse_sei_rbsp(rbsp: &[u8]) -> Vec<Vec<u8>> {
+    let mut user_data = Vec::new();
+    let mut position = 0;
+    // The last byte is the RBSP stop bit
+    while position + 1 < rbsp.len() {
+        let (Some(payload_type), Some(payload_size)) =
+            (read_sei_value(rbsp, &mut position), read_sei_value(rbsp, &mut position))
+        else {
+            break;
+        };
+        let Some(payload) = rbsp.get(position..position + payload_size as usize) else {
+            break;
+        };
+        position += payload_size as usize;
+
+        if payload_type == USER_DATA_UNREGISTERED && payload.len() > UUID_BYTES {
+            user_data.push(payload[UUID_BYTES..].to_vec());
+        }
+    }
+    user_data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_emulation_prevention_bytes() {
+        let data = [0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03, 0x00, 0x03];
+        assert_eq!(
+            remove_emulation_prevention(&data),
+            [0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x03]
+        );
+    }
+
+    #[test]
+    fn user_data_without_uuid() {
+        let mut rbsp = vec![0x01, 0x02, 0xaa, 0xbb];
+        rbsp.extend([USER_DATA_UNREGISTERED as u8, 19]);
+        rbsp.extend([0x11; UUID_BYTES]);
+        rbsp.extend([0x0f, 0x00, 0xff]);
+        rbsp.push(0x80);
+
+        assert_eq!(parse_sei_rbsp(&rbsp), [vec![0x0f, 0x00, 0xff]]);
+    }
+
+    #[test]
+    fn large_payload_size() {
+        let mut rbsp = vec![USER_DATA_UNREGISTERED as u8, 0xff, 0x01];
+        rbsp.extend([0x22; 256]);
+        rbsp.push(0x80);
+
+        let user_data = parse_sei_rbsp(&rbsp);
+        assert_eq!(user_data.len(), 1);
+        assert_eq!(user_data[0].len(), 256 - UUID_BYTES);
+    }
+
+    #[test]
+    fn overflowing_sei_value() {
+        let data = vec![0xff; (u32::MAX / 0xff) as usize + 1];
+        assert_eq!(read_sei_value(&data, &mut 0), None);
+    }
+}
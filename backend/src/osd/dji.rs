@@ -0,0 +1,241 @@
+//! Reader for the `.osd` files that DJI goggles running msp-osd (WTFOS) record next to the DVR video.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    error::OsdFileError,
+    fc_firmware::FcFirmware,
+    frame::Frame,
+    glyph::{Glyph, GridPosition, GridSize},
+    osd_file::{DiscardedData, OsdFile},
+    source::{has_extension, starts_with_magic, OsdSource, SourceContext},
+};
+
+const MAGIC: &[u8; 7] = b"MSPOSD\0";
+/// Magic, version and the OSD config: grid size, font size and offsets.
+const HEADER_BYTES: usize = 17;
+/// Versions from 2 on store the font variant after the config, as a firmware tag like the Walksnail header.
+const FONT_VARIANT_BYTES: usize = 5;
+/// Frame index and the number of glyphs in the frame.
+const FRAME_HEADER_BYTES: usize = 8;
+const BYTES_PER_GLYPH: usize = 2;
+/// msp-osd always records its whole canvas column by column, whatever the grid size that is used.
+const CANVAS_HEIGHT: usize = 22;
+/// The frame index counts video frames at this rate.
+const FRAME_INDEX_RATE: u64 = 60;
+
+pub struct DjiSource;
+
+impl OsdSource for DjiSource {
+    fn name(&self) -> &'static str {
+        "DJI"
+    }
+
+    fn probe(&self, path: &Path) -> bool {
+        has_extension(path, &["osd"]) && starts_with_magic(path, MAGIC)
+    }
+
+    #[tracing::instrument(skip(self, context), err)]
+    fn open(&self, path: &Path, context: &SourceContext) -> Result<OsdFile, OsdFileError> {
+        let bytes = fs::read(path)?;
+        read_dji_osd(path.to_path_buf(), &bytes, context.lenient)
+    }
+}
+
+fn read_dji_osd(path: PathBuf, bytes: &[u8], lenient: bool) -> Result<OsdFile, OsdFileError> {
+    if bytes.len() < HEADER_BYTES {
+        return Err(OsdFileError::TooShort { len: bytes.len() });
+    }
+
+    let version = u16::from_le_bytes([bytes[7], bytes[8]]);
+    let grid_size = GridSize {
+        width: bytes[9] as u32,
+        height: bytes[10] as u32,
+    };
+    if grid_size.width == 0 || grid_size.height == 0 {
+        return Err(OsdFileError::InvalidGridSize { grid_size });
+    }
+    let (fc_firmware, mut offset) = match version {
+        1 => (FcFirmware::Unknown, HEADER_BYTES),
+        2 | 3 => {
+            let variant = bytes
+                .get(HEADER_BYTES..HEADER_BYTES + 4)
+                .ok_or(OsdFileError::TooShort { len: bytes.len() })?;
+            (
+                FcFirmware::try_from(variant).unwrap_or(FcFirmware::Unknown),
+                HEADER_BYTES + FONT_VARIANT_BYTES,
+            )
+        }
+        _ => {
+            return Err(OsdFileError::UnsupportedVersion {
+                format: DjiSource.name(),
+                version,
+            })
+        }
+    };
+    tracing::info!("msp-osd recording version {version}, {grid_size} grid, {fc_firmware} font");
+
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut discarded = DiscardedData::default();
+    let mut index = 0;
+    while offset < bytes.len() {
+        let header = bytes.get(offset..offset + FRAME_HEADER_BYTES);
+        let frame_len = header.map_or(FRAME_HEADER_BYTES, |header| {
+            let glyph_count = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+            FRAME_HEADER_BYTES + glyph_count * BYTES_PER_GLYPH
+        });
+        let glyph_bytes = bytes.get(offset + FRAME_HEADER_BYTES..offset + frame_len);
+        let (Some(header), Some(glyph_bytes)) = (header, glyph_bytes) else {
+            if !lenient {
+                return Err(OsdFileError::TruncatedFrame {
+                    index,
+                    len: bytes.len() - offset,
+                    expected: frame_len,
+                });
+            }
+            discarded.bytes = bytes.len() - offset;
+            break;
+        };
+        offset += frame_len;
+
+        let frame_index = u32::from_le_bytes(header[..4].try_into().unwrap());
+        let time_millis = (frame_index as u64 * 1000 / FRAME_INDEX_RATE) as u32;
+        if let Some(previous) = frames.last() {
+            if time_millis < previous.time_millis {
+                if !lenient {
+                    return Err(OsdFileError::NonMonotonicTimestamp {
+                        index,
+                        time_millis,
+                        previous_millis: previous.time_millis,
+                    });
+                }
+                discarded.frames += 1;
+                index += 1;
+                continue;
+            }
+        }
+        frames.push(frame(time_millis, glyph_bytes, &grid_size));
+        index += 1;
+    }
+
+    if !discarded.is_empty() {
+        tracing::warn!("Discarded {} from {}", discarded, path.display());
+    }
+
+    Ok(OsdFile {
+        format_version: Some(version),
+        discarded,
+        ..OsdFile::from_frames(path, DjiSource.name(), fc_firmware, grid_size, frames)
+    })
+}
+
+/// Glyph indices have the font page in the high byte, like Walksnail. Glyphs outside of the grid that is in use are
+/// left over from other grid sizes and aren't shown by the goggles.
+fn frame(time_millis: u32, glyph_bytes: &[u8], grid_size: &GridSize) -> Frame {
+    let glyphs = glyph_bytes
+        .chunks_exact(BYTES_PER_GLYPH)
+        .enumerate()
+        .filter_map(|(i, glyph_bytes)| {
+            let index = u16::from_le_bytes([glyph_bytes[0], glyph_bytes[1]]);
+            let grid_position = GridPosition {
+                x: (i / CANVAS_HEIGHT) as u32,
+                y: (i % CANVAS_HEIGHT) as u32,
+            };
+            (index != 0x00 && index != 0x20 && grid_size.contains(&grid_position))
+                .then_some(Glyph { index, grid_position })
+        })
+        .collect();
+    Frame { time_millis, glyphs }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(version: u16) -> Vec<u8> {
+        let mut header = MAGIC.to_vec();
+        header.extend(version.to_le_bytes());
+        header.extend([53, 20, 24, 36, 0, 0, 0, 0]);
+        if version >= 2 {
+            header.extend(b"INAV\0");
+        }
+        header
+    }
+
+    fn frame_bytes(frame_index: u32, glyphs: &[(usize, usize, u16)]) -> Vec<u8> {
+        let mut canvas = vec![0u16; 60 * CANVAS_HEIGHT];
+        for &(x, y, index) in glyphs {
+            canvas[x * CANVAS_HEIGHT + y] = index;
+        }
+        let mut bytes = frame_index.to_le_bytes().to_vec();
+        bytes.extend((canvas.len() as u32).to_le_bytes());
+        bytes.extend(canvas.iter().flat_map(|index| index.to_le_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn read_column_major_canvas() {
+        let mut bytes = header(3);
+        bytes.extend(frame_bytes(0, &[(0, 0, 0x41), (2, 1, 0x142)]));
+        bytes.extend(frame_bytes(60, &[(52, 19, 0x43), (55, 21, 0x44)]));
+
+        let osd_file = read_dji_osd(PathBuf::new(), &bytes, false).unwrap();
+        assert_eq!(osd_file.format, "DJI");
+        assert_eq!(osd_file.fc_firmware, FcFirmware::Inav);
+        assert_eq!(osd_file.grid_size, GridSize::HD);
+        assert_eq!(osd_file.frames[1].time_millis, 1000);
+
+        let glyphs = osd_file
+            .frames
+            .iter()
+            .map(|frame| {
+                frame
+                    .glyphs
+                    .iter()
+                    .map(|g| (g.grid_position.x, g.grid_position.y, g.index))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(glyphs, [vec![(0, 0, 0x41), (2, 1, 0x142)], vec![(52, 19, 0x43)]]);
+    }
+
+    #[test]
+    fn version_1_without_font_variant() {
+        let mut bytes = header(1);
+        bytes.extend(frame_bytes(0, &[(0, 0, 0x41)]));
+
+        let osd_file = read_dji_osd(PathBuf::new(), &bytes, false).unwrap();
+        assert_eq!(osd_file.fc_firmware, FcFirmware::Unknown);
+        assert_eq!(osd_file.frame_count, 1);
+    }
+
+    #[test]
+    fn reject_empty_grid() {
+        let mut bytes = header(3);
+        bytes[10] = 0;
+        bytes.extend(frame_bytes(0, &[]));
+
+        assert!(matches!(
+            read_dji_osd(PathBuf::new(), &bytes, true),
+            Err(OsdFileError::InvalidGridSize { .. })
+        ));
+    }
+
+    #[test]
+    fn truncated_last_frame() {
+        let mut bytes = header(3);
+        bytes.extend(frame_bytes(0, &[]));
+        bytes.extend(&frame_bytes(1, &[])[..100]);
+
+        assert!(matches!(
+            read_dji_osd(PathBuf::new(), &bytes, false),
+            Err(OsdFileError::TruncatedFrame { index: 1, .. })
+        ));
+        let osd_file = read_dji_osd(PathBuf::new(), &bytes, true).unwrap();
+        assert_eq!(osd_file.frame_count, 1);
+        assert_eq!(osd_file.discarded, DiscardedData { frames: 0, bytes: 100 });
+    }
+}
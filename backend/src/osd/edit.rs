@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use super::{error::OsdFileError, frame::Frame, osd_file::OsdFile};
+
+impl OsdFile {
+    /// Keep the frames between `start` and `end`, with timestamps relative to `start`. The frame that is on screen at
+    /// `start` is kept as the first frame.
+    pub fn trim(&self, start: Duration, end: Duration) -> Result<Self, OsdFileError> {
+        if start >= end || start >= self.duration {
+            return Err(OsdFileError::NoFramesInRange { start, end });
+        }
+
+        let shifted = self.shift(-(start.as_millis() as i64));
+        let length_millis = (end - start).as_millis();
+        let frames = shifted
+            .frames
+            .into_iter()
+            .filter(|frame| u128::from(frame.time_millis) < length_millis)
+            .collect::<Vec<_>>();
+
+        if frames.is_empty() {
+            return Err(OsdFileError::NoFramesInRange { start, end });
+        }
+        Ok(self.with_frames(frames))
+    }
+
+    /// Move all frames by `offset_millis`, e.g. to sync the OSD with a video that started recording later. Frames that
+    /// end up before the start are dropped, except the last one which is moved to the start.
+    pub fn shift(&self, offset_millis: i64) -> Self {
+        let shifted = self
+            .frames
+            .iter()
+            .map(|frame| (i64::from(frame.time_millis) + offset_millis, frame))
+            .collect::<Vec<_>>();
+        let first_visible = shifted
+            .iter()
+            .rposition(|(time_millis, _)| *time_millis <= 0)
+            .unwrap_or(0);
+
+        let frames = shifted[first_visible..]
+            .iter()
+            .map(|(time_millis, frame)| Frame {
+                time_millis: (*time_millis).clamp(0, u32::MAX.into()) as u32,
+                glyphs: frame.glyphs.clone(),
+            })
+            .collect();
+        self.with_frames(frames)
+    }
+
+    /// Append the frames of `other` after the end of this file.
+    pub fn concat(&self, other: &OsdFile) -> Result<Self, OsdFileError> {
+        if self.grid_size != other.grid_size {
+            return Err(OsdFileError::GridSizeMismatch {
+                first: self.grid_size,
+                second: other.grid_size,
+            });
+        }
+        if self.fc_firmware != other.fc_firmware {
+            return Err(OsdFileError::FirmwareMismatch {
+                first: self.fc_firmware.clone(),
+                second: other.fc_firmware.clone(),
+            });
+        }
+
+        let offset = other.shift(self.duration.as_millis() as i64);
+        let frames = self.frames.iter().chain(offset.frames.iter()).cloned().collect();
+        Ok(self.with_frames(frames))
+    }
+
+    fn with_frames(&self, frames: Vec<Frame>) -> Self {
+        Self {
+            format_version: self.format_version,
+            ..Self::from_frames(
+                self.file_path.clone(),
+                self.format,
+                self.fc_firmware.clone(),
+                self.grid_size,
+                frames,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::osd::{FcFirmware, GridSize};
+
+    fn osd_file(timestamps: &[u32]) -> OsdFile {
+        let frames = timestamps
+            .iter()
+            .map(|&time_millis| Frame {
+                time_millis,
+                glyphs: Vec::new(),
+            })
+            .collect();
+        OsdFile::from_frames(
+            PathBuf::new(),
+            "Walksnail",
+            FcFirmware::Betaflight,
+            GridSize::HD,
+            frames,
+        )
+    }
+
+    fn timestamps(osd_file: &OsdFile) -> Vec<u32> {
+        osd_file.frames.iter().map(|f| f.time_millis).collect()
+    }
+
+    #[test]
+    fn trim_keeps_frame_on_screen_at_start() {
+        let trimmed = osd_file(&[0, 100, 200, 300, 400])
+            .trim(Duration::from_millis(150), Duration::from_millis(350))
+            .unwrap();
+        assert_eq!(timestamps(&trimmed), [0, 50, 150]);
+    }
+
+    #[test]
+    fn trim_outside_of_file() {
+        let result = osd_file(&[0, 100, 200]).trim(Duration::from_secs(1), Duration::from_secs(2));
+        assert!(matches!(result, Err(OsdFileError::NoFramesInRange { .. })));
+    }
+
+    #[test]
+    fn shift_forward_and_back() {
+        let osd_file = osd_file(&[0, 100, 200]);
+        assert_eq!(timestamps(&osd_file.shift(50)), [50, 150, 250]);
+        assert_eq!(timestamps(&osd_file.shift(-150)), [0, 50]);
+    }
+
+    #[test]
+    fn concat_after_end_of_first_file() {
+        let combined = osd_file(&[0, 100, 200]).concat(&osd_file(&[0, 100])).unwrap();
+        assert_eq!(timestamps(&combined), [0, 100, 200, 300, 400]);
+        assert_eq!(combined.frame_count, 5);
+    }
+}
@@ -0,0 +1,16 @@
+use std::path::Path;
+
+use super::artlynk::{self, ExtractionHandle};
+
+/// OSD data that is recorded inside the video file instead of in a separate `.osd` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddedOsd {
+    /// MSP DisplayPort packets in the SEI data of BetaFPV Artlynk recordings.
+    Artlynk,
+}
+
+/// Find out which kind of OSD data a video carries, based on the content of the video and not its file name.
+#[tracing::instrument(ret)]
+pub fn detect_embedded_osd(ffmpeg_path: &Path, video_path: &Path) -> Option<EmbeddedOsd> {
+    artlynk::contains_osd(ffmpeg_path, video_path, &ExtractionHandle::default()).then_some(EmbeddedOsd::Artlynk)
+}
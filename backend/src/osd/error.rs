@@ -1,12 +1,13 @@
+use std::{path::PathBuf, time::Duration};
+
 use thiserror::Error;
 
+use super::{FcFirmware, GridSize};
+
 #[derive(Error, Debug)]
 pub enum OsdFileError {
     // #[error("Unknown FC firmware: {0}")]
     // UnknownFcFirmware(String),
-
-    // #[error("Incomplete frame with index {index}")]
-    // IncompleteFrame { index: u32 },
     #[error("Malformed OSD file: {source}")]
     MalformedOsdFile {
         #[from]
@@ -18,4 +19,62 @@ pub enum OsdFileError {
         #[from]
         source: std::io::Error,
     },
+
+    #[error("Unable to write OSD file: {0}")]
+    UnableToWriteFile(std::io::Error),
+
+    #[error("OSD file is too short ({len} bytes) to contain a header and a frame")]
+    TooShort { len: usize },
+
+    #[error("Frame with index {index} is truncated ({len} of {expected} bytes)")]
+    TruncatedFrame { index: usize, len: usize, expected: usize },
+
+    #[error("Frame with index {index} has timestamp {time_millis} ms, which is before the previous frame at {previous_millis} ms")]
+    NonMonotonicTimestamp {
+        index: usize,
+        time_millis: u32,
+        previous_millis: u32,
+    },
+
+    #[error("No OSD frames between {start:?} and {end:?}")]
+    NoFramesInRange { start: Duration, end: Duration },
+
+    #[error("Can't combine OSD files from different flight controller firmware ({first} and {second})")]
+    FirmwareMismatch { first: FcFirmware, second: FcFirmware },
+
+    #[error("Can't combine OSD files with different grid sizes ({first} and {second})")]
+    GridSizeMismatch { first: GridSize, second: GridSize },
+
+    #[error("{path:?} is not in a supported OSD format")]
+    UnsupportedFormat { path: PathBuf },
+
+    #[error("{format} OSD file version {version} is not supported")]
+    UnsupportedVersion { format: &'static str, version: u16 },
+
+    #[error("Invalid OSD grid size {grid_size}")]
+    InvalidGridSize { grid_size: GridSize },
+
+    #[error("Only OSD data with the {} grid can be saved as .osd, not {grid_size}", GridSize::HD)]
+    UnsupportedGridSize { grid_size: GridSize },
+
+    #[error("No OSD data found in {path:?}")]
+    NoOsdData { path: PathBuf },
+}
+
+#[derive(Error, Debug)]
+pub enum Mp4Error {
+    #[error("Unable to read video file: {source}")]
+    UnableToReadFile {
+        #[from]
+        source: std::io::Error,
+    },
+
+    #[error("No {0} box found")]
+    MissingBox(&'static str),
+
+    #[error("Invalid {0} box")]
+    InvalidBox(&'static str),
+
+    #[error("No H.264 or H.265 video track found")]
+    NoVideoTrack,
 }
@@ -12,6 +12,20 @@ pub enum FcFirmware {
     Unknown,
 }
 
+impl FcFirmware {
+    /// The tag at the start of the `.osd` file header. Unknown firmware is written as zeros.
+    pub fn tag(&self) -> [u8; 4] {
+        match self {
+            FcFirmware::Betaflight => *b"BTFL",
+            FcFirmware::Inav => *b"INAV",
+            FcFirmware::ArduPilot => *b"ARDU",
+            FcFirmware::Kiss => *b"KISS",
+            FcFirmware::KissUltra => *b"ULTR",
+            FcFirmware::Unknown => [0; 4],
+        }
+    }
+}
+
 impl TryFrom<&str> for FcFirmware {
     type Error = OsdFileError;
 
@@ -2,47 +2,103 @@ use std::fmt::Debug;
 
 use super::{
     error::OsdFileError,
-    glyph::{Glyph, GridPosition},
+    glyph::{Glyph, GridPosition, GridSize},
 };
 
 const TIMESTAMP_BYTES: usize = 4;
 const BYTES_PER_GLYPH: usize = 2;
-const GRID_WIDTH: usize = 53;
-const _GRID_HEIGHT: usize = 20;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Frame {
     pub time_millis: u32,
     pub glyphs: Vec<Glyph>,
 }
 
-impl TryFrom<&[u8]> for Frame {
-    type Error = OsdFileError;
-
-    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        let time_millis = u32::from_le_bytes(value[..TIMESTAMP_BYTES].try_into().unwrap());
-        let glyphs = value[TIMESTAMP_BYTES..]
-            .chunks(BYTES_PER_GLYPH)
+impl Frame {
+    /// Build a frame from the glyph indices of a grid, row by row. Empty cells and spaces are left out.
+    pub(crate) fn from_grid(time_millis: u32, indices: impl IntoIterator<Item = u16>, grid_width: usize) -> Self {
+        let glyphs = indices
+            .into_iter()
             .enumerate()
-            .filter_map(|(idx, glyph_bytes)| {
-                let x = idx % GRID_WIDTH;
-                let y = idx / GRID_WIDTH;
-                let bytes = [glyph_bytes[0], glyph_bytes[1]];
-                let index = u16::from_le_bytes(bytes);
-                if index == 0x00 || index == 0x20 {
-                    None
-                } else {
-                    let glyph = Glyph {
-                        index,
-                        grid_position: GridPosition {
-                            x: x as u32,
-                            y: y as u32,
-                        },
-                    };
-                    Some(glyph)
-                }
+            .filter(|&(_, index)| index != 0x00 && index != 0x20)
+            .map(|(i, index)| Glyph {
+                index,
+                grid_position: GridPosition {
+                    x: (i % grid_width) as u32,
+                    y: (i / grid_width) as u32,
+                },
             })
             .collect();
-        Ok(Self { time_millis, glyphs })
+        Self { time_millis, glyphs }
+    }
+
+    /// Size of a frame in the Walksnail `.osd` layout: the timestamp followed by the glyph index of every cell.
+    pub(crate) fn byte_len(grid_size: &GridSize) -> usize {
+        TIMESTAMP_BYTES + (grid_size.width * grid_size.height) as usize * BYTES_PER_GLYPH
+    }
+
+    /// Serialize the frame in the Walksnail `.osd` layout. Glyphs outside of the grid are left out.
+    pub fn to_bytes(&self, grid_size: &GridSize) -> Vec<u8> {
+        let width = grid_size.width as usize;
+        let mut grid = vec![0u16; (grid_size.width * grid_size.height) as usize];
+        for glyph in self.glyphs.iter().filter(|g| grid_size.contains(&g.grid_position)) {
+            grid[glyph.grid_position.y as usize * width + glyph.grid_position.x as usize] = glyph.index;
+        }
+
+        let mut bytes = Vec::with_capacity(Self::byte_len(grid_size));
+        bytes.extend_from_slice(&self.time_millis.to_le_bytes());
+        for index in grid {
+            bytes.extend_from_slice(&index.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Read a frame in the Walksnail `.osd` layout. The index of the frame in the file is only used for errors.
+    pub(crate) fn from_bytes(index: usize, value: &[u8], grid_size: &GridSize) -> Result<Self, OsdFileError> {
+        let expected = Self::byte_len(grid_size);
+        if value.len() != expected {
+            return Err(OsdFileError::TruncatedFrame {
+                index,
+                len: value.len(),
+                expected,
+            });
+        }
+
+        let time_millis = u32::from_le_bytes(value[..TIMESTAMP_BYTES].try_into().unwrap());
+        let indices = value[TIMESTAMP_BYTES..]
+            .chunks(BYTES_PER_GLYPH)
+            .map(|glyph_bytes| u16::from_le_bytes([glyph_bytes[0], glyph_bytes[1]]));
+        Ok(Self::from_grid(time_millis, indices, grid_size.width as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_sd_grid() {
+        let grid_size = GridSize { width: 30, height: 16 };
+        let frame = Frame {
+            time_millis: 100,
+            glyphs: vec![
+                Glyph {
+                    index: 0x41,
+                    grid_position: GridPosition { x: 29, y: 0 },
+                },
+                Glyph {
+                    index: 0x142,
+                    grid_position: GridPosition { x: 0, y: 15 },
+                },
+            ],
+        };
+
+        let bytes = frame.to_bytes(&grid_size);
+        assert_eq!(bytes.len(), Frame::byte_len(&grid_size));
+        assert_eq!(Frame::from_bytes(0, &bytes, &grid_size).unwrap(), frame);
+        assert!(matches!(
+            Frame::from_bytes(7, &bytes, &GridSize::HD),
+            Err(OsdFileError::TruncatedFrame { index: 7, .. })
+        ));
     }
 }
@@ -1,10 +1,30 @@
 use std::fmt::{Debug, Display};
 
-use crate::util::Coordinates;
+use crate::util::{Coordinates, Dimension};
 
 pub type GridPosition = Coordinates<u32>;
+/// Number of columns and rows of the OSD grid.
+pub type GridSize = Dimension<u32>;
 
-#[derive(Debug, Clone)]
+impl GridSize {
+    /// The Walksnail HD grid, which the font character sizes are made for.
+    pub const HD: Self = Self { width: 53, height: 20 };
+
+    pub fn contains(&self, position: &GridPosition) -> bool {
+        position.x < self.width && position.y < self.height
+    }
+
+    /// Horizontal and vertical factor to scale characters by, so the grid covers the same area of the video as the HD
+    /// grid.
+    pub fn character_scale(&self) -> (f32, f32) {
+        (
+            Self::HD.width as f32 / self.width.max(1) as f32,
+            Self::HD.height as f32 / self.height.max(1) as f32,
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Glyph {
     pub index: u16,
     pub grid_position: GridPosition,
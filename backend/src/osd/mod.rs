@@ -1,13 +1,26 @@
 pub mod artlynk;
+mod dji;
+mod edit;
+mod embedded;
 mod error;
 mod fc_firmware;
 mod frame;
 mod glyph;
 mod options;
 mod osd_file;
+mod redact;
+mod source;
+pub mod telemetry;
+mod timed_mask;
 
+pub use dji::DjiSource;
+pub use embedded::{detect_embedded_osd, EmbeddedOsd};
 pub use error::OsdFileError;
 pub use fc_firmware::FcFirmware;
 pub use frame::Frame;
+pub use glyph::{Glyph, GridPosition, GridSize};
 pub use options::OsdOptions;
-pub use osd_file::OsdFile;
+pub use osd_file::{DiscardedData, OsdFile, WalksnailSource};
+pub use redact::{RedactionAction, RedactionPattern, RedactionRule};
+pub use source::{OsdSource, OsdSourceRegistry, SourceContext};
+pub use timed_mask::{FlightState, MaskTarget, MaskTrigger, TimedMask};
@@ -1,8 +1,13 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::RangeInclusive};
 
 use derivative::Derivative;
 use serde::{Deserialize, Serialize};
 
+use super::{
+    glyph::Glyph,
+    redact::RedactionRule,
+    timed_mask::{FlightState, TimedMask},
+};
 use crate::util::Coordinates;
 
 #[derive(Clone, Serialize, Deserialize, Derivative)]
@@ -15,6 +20,17 @@ pub struct OsdOptions {
     #[serde(skip)]
     pub osd_playback_speed_factor: f32,
     pub masked_grid_positions: HashSet<Coordinates<u32>>,
+    /// Font characters that are hidden wherever they are drawn, for elements that move around like the home arrow.
+    #[serde(default)]
+    pub masked_glyphs: HashSet<u16>,
+    #[serde(default)]
+    pub masked_glyph_ranges: Vec<RangeInclusive<u16>>,
+    /// Text that is removed or replaced wherever it shows up, see [`RedactionRule`].
+    #[serde(default)]
+    pub redaction_rules: Vec<RedactionRule>,
+    /// Masks that only apply part of the time, see [`MaskTrigger`](super::MaskTrigger).
+    #[serde(default)]
+    pub timed_masks: Vec<TimedMask>,
     #[derivative(Default(value = "100.0"))]
     pub scale: f32,
 }
@@ -35,4 +51,75 @@ impl OsdOptions {
     pub fn reset_mask(&mut self) {
         self.masked_grid_positions.clear();
     }
+
+    /// Whether the glyph should be left out, either because of its position or the character it shows. Timed masks are
+    /// evaluated for the flight state of the frame that is drawn.
+    pub fn is_masked(&self, glyph: &Glyph, flight_state: &FlightState) -> bool {
+        self.get_mask(&glyph.grid_position)
+            || self.get_glyph_mask(glyph.index)
+            || self.timed_masks.iter().any(|mask| mask.is_masked(glyph, flight_state))
+    }
+
+    pub fn get_glyph_mask(&self, index: u16) -> bool {
+        self.masked_glyphs.contains(&index) || self.masked_glyph_ranges.iter().any(|range| range.contains(&index))
+    }
+
+    /// Unmasking a glyph that is part of a range splits the range around it.
+    pub fn toggle_glyph_mask(&mut self, index: u16) {
+        if !self.get_glyph_mask(index) {
+            self.masked_glyphs.insert(index);
+            return;
+        }
+
+        self.masked_glyphs.remove(&index);
+        self.masked_glyph_ranges = self
+            .masked_glyph_ranges
+            .iter()
+            .flat_map(|range| {
+                if !range.contains(&index) {
+                    return vec![range.clone()];
+                }
+                let mut parts = Vec::new();
+                if index > *range.start() {
+                    parts.push(*range.start()..=index - 1);
+                }
+                if index < *range.end() {
+                    parts.push(index + 1..=*range.end());
+                }
+                parts
+            })
+            .collect();
+    }
+
+    pub fn mask_glyph_range(&mut self, range: RangeInclusive<u16>) {
+        if !range.is_empty() {
+            self.masked_glyph_ranges.push(range);
+        }
+    }
+
+    pub fn reset_glyph_mask(&mut self) {
+        self.masked_glyphs.clear();
+        self.masked_glyph_ranges.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unmask_glyph_in_range() {
+        let mut options = OsdOptions::default();
+        options.mask_glyph_range(0x80..=0x8f);
+        options.toggle_glyph_mask(0x05);
+        assert!(options.get_glyph_mask(0x05));
+        assert!(options.get_glyph_mask(0x89));
+
+        options.toggle_glyph_mask(0x89);
+        options.toggle_glyph_mask(0x80);
+        options.toggle_glyph_mask(0x05);
+        assert!(!options.get_glyph_mask(0x05));
+        assert!(!options.get_glyph_mask(0x89));
+        assert_eq!(options.masked_glyph_ranges, [0x81..=0x88, 0x8a..=0x8f]);
+    }
 }
@@ -1,49 +1,318 @@
-use std::{fs, path::PathBuf, time::Duration};
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use derivative::Derivative;
 
-use super::{error::OsdFileError, fc_firmware::FcFirmware};
+use super::{
+    error::OsdFileError,
+    fc_firmware::FcFirmware,
+    glyph::GridSize,
+    source::{has_extension, OsdSource, SourceContext},
+};
 use crate::osd::frame::Frame;
 
+/// Only the firmware tag at the start of the header is known, the rest is ignored when reading and zero when writing.
+/// The layout of the other 36 bytes isn't documented, so they aren't decoded until it's worked out from recordings of
+/// different goggle and flight controller firmware versions.
 const HEADER_BYTES: usize = 40;
 const FC_TYPE_BYTES: usize = 4;
-const FRAME_BYTES: usize = 2124;
 
 #[derive(Derivative, Clone)]
 #[derivative(Debug)]
 pub struct OsdFile {
     pub file_path: PathBuf,
+    /// Name of the [`OsdSource`](super::OsdSource) the file was read with.
+    pub format: &'static str,
     pub fc_firmware: FcFirmware,
+    /// Version of the file format, for formats that have one.
+    pub format_version: Option<u16>,
+    /// Data of a damaged file that was skipped because it was opened with [`SourceContext::lenient`].
+    pub discarded: DiscardedData,
+    /// Size of the grid the OSD was drawn on by the goggles.
+    pub grid_size: GridSize,
     pub frame_count: u32,
     pub duration: Duration,
     #[derivative(Debug = "ignore")]
     pub frames: Vec<Frame>,
 }
 
+/// The `.osd` files that Walksnail goggles and VRXs record next to the video. These files don't start with a magic
+/// number, so every `.osd` file is accepted. Formats that can be recognized by their header must be registered before
+/// this one.
+pub struct WalksnailSource;
+
+impl OsdSource for WalksnailSource {
+    fn name(&self) -> &'static str {
+        "Walksnail"
+    }
+
+    fn probe(&self, path: &Path) -> bool {
+        has_extension(path, &["osd"])
+    }
+
+    fn open(&self, path: &Path, context: &SourceContext) -> Result<OsdFile, OsdFileError> {
+        if context.lenient {
+            OsdFile::open_lenient(path.to_path_buf())
+        } else {
+            OsdFile::open(path.to_path_buf())
+        }
+    }
+}
+
+/// Data that was skipped when opening a damaged OSD file leniently, e.g. with [`OsdFile::open_lenient`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiscardedData {
+    /// Frames that were dropped because their timestamp went backwards.
+    pub frames: u32,
+    /// Bytes of the incomplete frame at the end of the file.
+    pub bytes: usize,
+}
+
+impl DiscardedData {
+    pub fn is_empty(&self) -> bool {
+        self.frames == 0 && self.bytes == 0
+    }
+}
+
+impl Display for DiscardedData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} frames and {} trailing bytes", self.frames, self.bytes)
+    }
+}
+
 impl OsdFile {
+    /// Open an OSD file, failing on any truncated frame or timestamp that goes backwards.
     #[tracing::instrument(ret, err)]
     pub fn open(path: PathBuf) -> Result<Self, OsdFileError> {
-        let mut bytes = fs::read(&path)?;
-        let header_bytes = bytes.drain(0..HEADER_BYTES).collect::<Vec<u8>>();
+        let bytes = fs::read(&path)?;
+        Self::from_bytes(path, &bytes, false)
+    }
+
+    /// Open an OSD file, keeping every complete frame. Goggles that lose power while recording leave a partial frame
+    /// at the end of the file, this drops it instead of failing. What was dropped is in [`Self::discarded`].
+    #[tracing::instrument(ret, err)]
+    pub fn open_lenient(path: PathBuf) -> Result<Self, OsdFileError> {
+        let bytes = fs::read(&path)?;
+        Self::from_bytes(path, &bytes, true)
+    }
+
+    fn from_bytes(path: PathBuf, bytes: &[u8], lenient: bool) -> Result<Self, OsdFileError> {
+        // Walksnail goggles always record the HD grid
+        let grid_size = GridSize::HD;
+        let frame_len = Frame::byte_len(&grid_size);
+        if bytes.len() < HEADER_BYTES + frame_len {
+            return Err(OsdFileError::TooShort { len: bytes.len() });
+        }
+
+        let (header_bytes, frame_bytes) = bytes.split_at(HEADER_BYTES);
         let fc_firmware = FcFirmware::try_from(&header_bytes[..FC_TYPE_BYTES])?;
 
-        let frames = bytes
-            .chunks(FRAME_BYTES)
-            .map(|frame_bytes| frame_bytes.try_into().unwrap())
-            .collect::<Vec<Frame>>();
+        let mut discarded = DiscardedData::default();
+        let mut frames: Vec<Frame> = Vec::with_capacity(frame_bytes.len() / frame_len);
+        for (index, chunk) in frame_bytes.chunks(frame_len).enumerate() {
+            if chunk.len() < frame_len {
+                if !lenient {
+                    return Err(OsdFileError::TruncatedFrame {
+                        index,
+                        len: chunk.len(),
+                        expected: frame_len,
+                    });
+                }
+                discarded.bytes += chunk.len();
+                continue;
+            }
 
-        let frame_interval = (frames.last().unwrap().time_millis - frames.first().unwrap().time_millis) as f32
-            / (frames.len() - 1) as f32;
+            let frame = Frame::from_bytes(index, chunk, &grid_size)?;
+            if let Some(previous) = frames.last() {
+                if frame.time_millis < previous.time_millis {
+                    if !lenient {
+                        return Err(OsdFileError::NonMonotonicTimestamp {
+                            index,
+                            time_millis: frame.time_millis,
+                            previous_millis: previous.time_millis,
+                        });
+                    }
+                    discarded.frames += 1;
+                    continue;
+                }
+            }
+            frames.push(frame);
+        }
 
-        let duration = Duration::from_millis(frames.last().unwrap().time_millis.into())
-            + Duration::from_secs_f32(frame_interval / 1000.0);
+        if !discarded.is_empty() {
+            tracing::warn!("Discarded {} from {}", discarded, path.display());
+        }
 
         Ok(Self {
+            discarded,
+            ..Self::from_frames(path, WalksnailSource.name(), fc_firmware, grid_size, frames)
+        })
+    }
+
+    /// Create an OSD file from frames that are sorted by time. The duration includes the display time of the last
+    /// frame, which is assumed to be the average frame interval.
+    pub fn from_frames(
+        path: PathBuf,
+        format: &'static str,
+        fc_firmware: FcFirmware,
+        grid_size: GridSize,
+        frames: Vec<Frame>,
+    ) -> Self {
+        let duration = match (frames.first(), frames.last()) {
+            (Some(first), Some(last)) => {
+                let frame_interval = if frames.len() > 1 {
+                    (last.time_millis - first.time_millis) as f32 / (frames.len() - 1) as f32
+                } else {
+                    0.0
+                };
+                Duration::from_millis(last.time_millis.into()) + Duration::from_secs_f32(frame_interval / 1000.0)
+            }
+            _ => Duration::ZERO,
+        };
+
+        Self {
             file_path: path,
+            format,
             fc_firmware,
+            format_version: None,
+            discarded: DiscardedData::default(),
+            grid_size,
             frame_count: frames.len() as u32,
             duration,
             frames,
-        })
+        }
+    }
+
+    /// Serialize the file in the Walksnail `.osd` format, which has the HD grid. Other grids are rejected, they would
+    /// lose glyphs or be read back at the wrong scale.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, OsdFileError> {
+        if self.grid_size != GridSize::HD {
+            return Err(OsdFileError::UnsupportedGridSize {
+                grid_size: self.grid_size,
+            });
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_BYTES + self.frames.len() * Frame::byte_len(&GridSize::HD));
+        bytes.extend_from_slice(&self.fc_firmware.tag());
+        bytes.resize(HEADER_BYTES, 0);
+        for frame in &self.frames {
+            bytes.extend(frame.to_bytes(&GridSize::HD));
+        }
+        Ok(bytes)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub fn write(&self, path: &Path) -> Result<(), OsdFileError> {
+        fs::write(path, self.to_bytes()?).map_err(OsdFileError::UnableToWriteFile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> Vec<u8> {
+        let mut header = b"BTFL".to_vec();
+        header.resize(HEADER_BYTES, 0);
+        header
+    }
+
+    fn frame(time_millis: u32) -> Vec<u8> {
+        let mut frame = time_millis.to_le_bytes().to_vec();
+        frame.resize(Frame::byte_len(&GridSize::HD), 0);
+        frame
+    }
+
+    fn osd_bytes(timestamps: &[u32]) -> Vec<u8> {
+        let mut bytes = header();
+        for &time_millis in timestamps {
+            bytes.extend(frame(time_millis));
+        }
+        bytes
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let bytes = osd_bytes(&[0, 100, 200]);
+        let osd_file = OsdFile::from_bytes(PathBuf::new(), &bytes, false).unwrap();
+        assert_eq!(osd_file.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn reject_saving_other_grid_sizes() {
+        let grid_size = GridSize { width: 60, height: 22 };
+        let frames = vec![Frame::from_grid(0, [0x41; 60 * 22], 60)];
+        let osd_file = OsdFile::from_frames(PathBuf::new(), "DJI", FcFirmware::Betaflight, grid_size, frames);
+        assert!(matches!(
+            osd_file.to_bytes(),
+            Err(OsdFileError::UnsupportedGridSize { grid_size: g }) if g == grid_size
+        ));
+    }
+
+    #[test]
+    fn ignore_unknown_header_bytes() {
+        let mut bytes = osd_bytes(&[0, 100]);
+        bytes[FC_TYPE_BYTES..HEADER_BYTES].fill(0xff);
+
+        let osd_file = OsdFile::from_bytes(PathBuf::new(), &bytes, false).unwrap();
+        assert_eq!(osd_file.fc_firmware, FcFirmware::Betaflight);
+        assert_eq!(osd_file.format_version, None);
+        assert_eq!(osd_file.grid_size, GridSize::HD);
+        assert_eq!(osd_file.frame_count, 2);
+    }
+
+    #[test]
+    fn reject_file_without_frames() {
+        for bytes in [Vec::new(), header()] {
+            let result = OsdFile::from_bytes(PathBuf::new(), &bytes, true);
+            assert!(matches!(result, Err(OsdFileError::TooShort { .. })));
+        }
+    }
+
+    #[test]
+    fn open_single_frame_file() {
+        let osd_file = OsdFile::from_bytes(PathBuf::new(), &osd_bytes(&[500]), false).unwrap();
+        assert_eq!(osd_file.frame_count, 1);
+        assert_eq!(osd_file.duration, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn truncated_last_frame() {
+        let mut bytes = osd_bytes(&[0, 100, 200]);
+        bytes.extend(&frame(300)[..1001]);
+
+        let result = OsdFile::from_bytes(PathBuf::new(), &bytes, false);
+        assert!(matches!(
+            result,
+            Err(OsdFileError::TruncatedFrame {
+                index: 3,
+                len: 1001,
+                ..
+            })
+        ));
+
+        let osd_file = OsdFile::from_bytes(PathBuf::new(), &bytes, true).unwrap();
+        assert_eq!(osd_file.frame_count, 3);
+        assert_eq!(osd_file.duration.as_millis(), 300);
+        assert_eq!(osd_file.discarded, DiscardedData { frames: 0, bytes: 1001 });
+    }
+
+    #[test]
+    fn non_monotonic_timestamps() {
+        let bytes = osd_bytes(&[0, 100, 50, 200]);
+
+        let result = OsdFile::from_bytes(PathBuf::new(), &bytes, false);
+        assert!(matches!(
+            result,
+            Err(OsdFileError::NonMonotonicTimestamp { index: 2, .. })
+        ));
+
+        let osd_file = OsdFile::from_bytes(PathBuf::new(), &bytes, true).unwrap();
+        assert_eq!(osd_file.frame_count, 3);
+        assert_eq!(osd_file.discarded, DiscardedData { frames: 1, bytes: 0 });
     }
 }
@@ -0,0 +1,232 @@
+//! Hide OSD text by what it says instead of where it is, for elements like the pilot name and GPS coordinates that
+//! move between flights.
+
+use std::{borrow::Cow, collections::BTreeMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use super::frame::Frame;
+
+/// Decimals of a number from which on it is taken for a GPS coordinate. Flight controllers show coordinates with 6 or
+/// 7 decimals, other values with 2 at most.
+const COORDINATE_DECIMALS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedactionPattern {
+    /// Text on the OSD, e.g. the pilot or craft name. Letters are matched regardless of case.
+    Text(String),
+    /// Numbers with enough decimals to be GPS coordinates.
+    Coordinates,
+}
+
+impl Display for RedactionPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(text) => write!(f, "\"{text}\""),
+            Self::Coordinates => write!(f, "GPS coordinates"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedactionAction {
+    Remove,
+    /// Draw this character in place of every character of the match.
+    Replace(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionRule {
+    pub pattern: RedactionPattern,
+    pub action: RedactionAction,
+}
+
+impl RedactionRule {
+    /// Ranges of cells in `row` that match the pattern.
+    fn matches(&self, row: &[char]) -> Vec<std::ops::Range<usize>> {
+        match &self.pattern {
+            RedactionPattern::Text(text) => {
+                let text = text.chars().collect::<Vec<_>>();
+                if text.is_empty() || text.len() > row.len() {
+                    return Vec::new();
+                }
+                (0..=row.len() - text.len())
+                    .filter(|&start| {
+                        row[start..start + text.len()]
+                            .iter()
+                            .zip(&text)
+                            .all(|(a, b)| a.eq_ignore_ascii_case(b))
+                    })
+                    .map(|start| start..start + text.len())
+                    .collect()
+            }
+            RedactionPattern::Coordinates => coordinates(row),
+        }
+    }
+}
+
+impl Frame {
+    /// Apply the redaction rules to the glyphs of the frame. Letters, digits and punctuation are at their ASCII
+    /// positions in all fonts, so the text is read from the glyph indices directly.
+    pub fn redact(&self, rules: &[RedactionRule]) -> Cow<'_, Frame> {
+        if rules.is_empty() {
+            return Cow::Borrowed(self);
+        }
+
+        // Index into `self.glyphs` of every cell, row by row
+        let mut rows = BTreeMap::<u32, Vec<Option<usize>>>::new();
+        for (i, glyph) in self.glyphs.iter().enumerate() {
+            let row = rows.entry(glyph.grid_position.y).or_default();
+            let x = glyph.grid_position.x as usize;
+            if row.len() <= x {
+                row.resize(x + 1, None);
+            }
+            row[x] = Some(i);
+        }
+
+        let mut actions = vec![None; self.glyphs.len()];
+        for cells in rows.values() {
+            let text = cells
+                .iter()
+                .map(|cell| match cell {
+                    Some(i) => glyph_char(self.glyphs[*i].index),
+                    None => ' ',
+                })
+                .collect::<Vec<_>>();
+            for rule in rules {
+                for range in rule.matches(&text) {
+                    for i in cells[range].iter().flatten() {
+                        actions[*i] = Some(rule.action);
+                    }
+                }
+            }
+        }
+
+        if actions.iter().all(Option::is_none) {
+            return Cow::Borrowed(self);
+        }
+        let glyphs = self
+            .glyphs
+            .iter()
+            .zip(actions)
+            .filter_map(|(glyph, action)| match action {
+                None => Some(glyph.clone()),
+                Some(RedactionAction::Remove) => None,
+                Some(RedactionAction::Replace(c)) => {
+                    let mut glyph = glyph.clone();
+                    glyph.index = c as u16;
+                    Some(glyph)
+                }
+            })
+            .collect();
+        Cow::Owned(Frame {
+            time_millis: self.time_millis,
+            glyphs,
+        })
+    }
+}
+
+/// Symbols and graphics are read as a control character so they never match text.
+fn glyph_char(index: u16) -> char {
+    match char::from_u32(index.into()) {
+        Some(c) if c.is_ascii_graphic() => c,
+        _ => '\u{0}',
+    }
+}
+
+/// Numbers like `-8.5455938`: an optional sign, digits, a decimal point and at least [`COORDINATE_DECIMALS`] digits.
+fn coordinates(row: &[char]) -> Vec<std::ops::Range<usize>> {
+    let mut matches = Vec::new();
+    let mut i = 0;
+    while i < row.len() {
+        let start = i;
+        if row[i] == '-' {
+            i += 1;
+        }
+        let integer_start = i;
+        while i < row.len() && row[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i == integer_start || i >= row.len() || row[i] != '.' {
+            i = start + 1;
+            continue;
+        }
+        let decimals_start = i + 1;
+        i = decimals_start;
+        while i < row.len() && row[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i - decimals_start >= COORDINATE_DECIMALS {
+            matches.push(start..i);
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osd::glyph::{Glyph, GridPosition};
+
+    fn frame(rows: &[(u32, u32, &str)]) -> Frame {
+        let glyphs = rows
+            .iter()
+            .flat_map(|&(x, y, text)| {
+                text.bytes()
+                    .enumerate()
+                    .filter(|(_, b)| *b != b' ')
+                    .map(move |(i, b)| Glyph {
+                        index: b.into(),
+                        grid_position: GridPosition { x: x + i as u32, y },
+                    })
+            })
+            .collect();
+        Frame { time_millis: 0, glyphs }
+    }
+
+    fn text(frame: &Frame, y: u32) -> String {
+        let mut glyphs = frame
+            .glyphs
+            .iter()
+            .filter(|g| g.grid_position.y == y)
+            .collect::<Vec<_>>();
+        glyphs.sort_by_key(|g| g.grid_position.x);
+        glyphs.iter().map(|g| g.index as u8 as char).collect()
+    }
+
+    #[test]
+    fn remove_text_across_spaces() {
+        let frame = frame(&[(3, 0, "12.4V"), (10, 0, "Jane Doe"), (2, 5, "JANE")]);
+        let rules = [RedactionRule {
+            pattern: RedactionPattern::Text("jane doe".into()),
+            action: RedactionAction::Remove,
+        }];
+
+        let redacted = frame.redact(&rules);
+        assert_eq!(text(&redacted, 0), "12.4V");
+        assert_eq!(text(&redacted, 5), "JANE");
+    }
+
+    #[test]
+    fn replace_coordinates() {
+        let frame = frame(&[(1, 1, "\x0147.3977419  -8.5455938"), (1, 2, "16.24 -1.5")]);
+        let rules = [RedactionRule {
+            pattern: RedactionPattern::Coordinates,
+            action: RedactionAction::Replace('*'),
+        }];
+
+        let redacted = frame.redact(&rules);
+        assert_eq!(text(&redacted, 1), format!("\x01{}", "*".repeat(20)));
+        assert_eq!(text(&redacted, 2), "16.24-1.5");
+    }
+
+    #[test]
+    fn unchanged_frame_is_borrowed() {
+        let frame = frame(&[(1, 1, "ACRO")]);
+        let rules = [RedactionRule {
+            pattern: RedactionPattern::Coordinates,
+            action: RedactionAction::Remove,
+        }];
+        assert!(matches!(frame.redact(&rules), Cow::Borrowed(_)));
+    }
+}
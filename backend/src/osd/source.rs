@@ -0,0 +1,114 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    artlynk::{ArtlynkSource, ExtractionHandle},
+    dji::DjiSource,
+    error::OsdFileError,
+    osd_file::{OsdFile, WalksnailSource},
+};
+
+/// Everything a source might need to read OSD data, besides the path.
+#[derive(Debug, Clone, Default)]
+pub struct SourceContext {
+    /// Used by sources that extract the OSD from a video.
+    pub ffmpeg_path: PathBuf,
+    /// Keep the complete frames of damaged files instead of failing.
+    pub lenient: bool,
+    /// Directory to cache slow extractions in.
+    pub cache_dir: Option<PathBuf>,
+    /// Progress and cancellation of slow extractions.
+    pub handle: ExtractionHandle,
+}
+
+/// A format that OSD data can be read from. The metadata and frames are returned as an [`OsdFile`], whatever the
+/// format, so the rest of the app doesn't have to know where they came from.
+pub trait OsdSource: Send + Sync {
+    /// Name of the format that is shown to the user.
+    fn name(&self) -> &'static str;
+
+    /// Whether the file is in this format. Should be fast, e.g. only check the extension and the first bytes.
+    fn probe(&self, path: &Path) -> bool;
+
+    fn open(&self, path: &Path, context: &SourceContext) -> Result<OsdFile, OsdFileError>;
+}
+
+/// The supported OSD formats, in the order they are probed.
+pub struct OsdSourceRegistry {
+    sources: Vec<Box<dyn OsdSource>>,
+}
+
+impl Default for OsdSourceRegistry {
+    fn default() -> Self {
+        let mut registry = Self { sources: Vec::new() };
+        // Walksnail files have no magic number, so formats that do have to be probed first
+        registry.register(Box::new(DjiSource));
+        registry.register(Box::new(WalksnailSource));
+        registry.register(Box::new(ArtlynkSource));
+        registry
+    }
+}
+
+impl OsdSourceRegistry {
+    pub fn register(&mut self, source: Box<dyn OsdSource>) {
+        self.sources.push(source);
+    }
+
+    pub fn sources(&self) -> impl Iterator<Item = &dyn OsdSource> {
+        self.sources.iter().map(|source| source.as_ref())
+    }
+
+    /// The first source that can read the file.
+    pub fn probe(&self, path: &Path) -> Option<&dyn OsdSource> {
+        self.sources().find(|source| source.probe(path))
+    }
+
+    /// Read the file with the first source that recognizes it. The format that matched is stored in
+    /// [`OsdFile::format`].
+    #[tracing::instrument(skip(self, context), err)]
+    pub fn open(&self, path: &Path, context: &SourceContext) -> Result<OsdFile, OsdFileError> {
+        let source = self.probe(path).ok_or_else(|| OsdFileError::UnsupportedFormat {
+            path: path.to_path_buf(),
+        })?;
+        tracing::info!("Reading {} OSD data from {}", source.name(), path.display());
+        source.open(path, context)
+    }
+}
+
+/// Whether the file starts with the magic number of a format.
+pub(crate) fn starts_with_magic(path: &Path, magic: &[u8]) -> bool {
+    let mut bytes = vec![0u8; magic.len()];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut bytes))
+        .is_ok_and(|_| bytes == magic)
+}
+
+/// Whether the file has one of the extensions, ignoring case.
+pub(crate) fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .is_some_and(|e| extensions.iter().any(|ext| e.eq_ignore_ascii_case(ext)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_by_extension() {
+        let registry = OsdSourceRegistry::default();
+        let name = |path: &str| registry.probe(Path::new(path)).map(|source| source.name());
+
+        assert_eq!(name("AvatarG0001.osd"), Some("Walksnail"));
+        assert_eq!(name("Artlynk_0001.MP4"), Some("Artlynk"));
+        assert_eq!(name("AvatarG0001.srt"), None);
+    }
+
+    #[test]
+    fn unsupported_format() {
+        let result = OsdSourceRegistry::default().open(Path::new("font.png"), &SourceContext::default());
+        assert!(matches!(result, Err(OsdFileError::UnsupportedFormat { .. })));
+    }
+}
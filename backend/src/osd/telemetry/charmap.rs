@@ -0,0 +1,138 @@
+//! What the glyphs of the flight controller fonts stand for. Letters, digits and punctuation are at their ASCII
+//! positions in all fonts, the symbols differ per firmware. Only the symbols that the telemetry recognisers need are
+//! mapped.
+
+use std::collections::HashMap;
+
+use crate::osd::FcFirmware;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symbol {
+    Rssi,
+    LinkQuality,
+    Volt,
+    Amp,
+    MilliampHours,
+    /// In front of the altitude, the unit follows the value.
+    Altitude,
+    Meter,
+    Feet,
+    /// Altitude value unit, for firmware that combines the altitude icon with the unit.
+    AltitudeMeter,
+    AltitudeFeet,
+    KilometersPerHour,
+    MilesPerHour,
+    MetersPerSecond,
+    Latitude,
+    Longitude,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OsdChar {
+    Text(char),
+    Symbol(Symbol),
+    /// Icons and graphics that don't carry a value, like the artificial horizon.
+    Other,
+}
+
+impl OsdChar {
+    pub fn is_text(&self, c: char) -> bool {
+        *self == OsdChar::Text(c)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CharMap {
+    symbols: HashMap<u16, Symbol>,
+    /// Flight mode names the firmware shows.
+    flight_modes: &'static [&'static str],
+}
+
+impl CharMap {
+    /// KISS and unknown firmware use the Betaflight font layout.
+    pub fn for_firmware(fc_firmware: &FcFirmware) -> Self {
+        use Symbol::*;
+        let (symbols, flight_modes): (&[(u16, Symbol)], &[&str]) = match fc_firmware {
+            FcFirmware::Inav => (
+                &[
+                    (0x01, Rssi),
+                    (0x02, LinkQuality),
+                    (0x03, Latitude),
+                    (0x04, Longitude),
+                    (0x1f, Volt),
+                    (0x6a, Amp),
+                    (0x99, MilliampHours),
+                    (0x76, AltitudeMeter),
+                    (0x78, AltitudeFeet),
+                    (0xa1, KilometersPerHour),
+                    (0xa2, MilesPerHour),
+                    (0x9f, MetersPerSecond),
+                ],
+                &[
+                    "ACRO", "ANGL", "HOR", "MANU", "RTH", "WP", "CRS", "CRUZ", "HOLD", "POSH", "ALTH", "LAND", "3CRS",
+                    "TURT", "LOTR", "!FS!",
+                ],
+            ),
+            FcFirmware::ArduPilot => (
+                &[
+                    (0x01, Rssi),
+                    (0xf8, LinkQuality),
+                    (0xa6, Latitude),
+                    (0xa7, Longitude),
+                    (0x06, Volt),
+                    (0x9a, Amp),
+                    (0x07, MilliampHours),
+                    (0xb1, AltitudeMeter),
+                    (0xb3, AltitudeFeet),
+                    (0xa1, KilometersPerHour),
+                    (0xa2, MilesPerHour),
+                    (0x9f, MetersPerSecond),
+                ],
+                &[
+                    "STAB", "ACRO", "ALTH", "AUTO", "GUID", "LOIT", "RTL", "CIRC", "LAND", "DRFT", "SPRT", "FLIP",
+                    "ATUN", "POSH", "BRAK", "THRW", "SRTL", "FBWA", "FBWB", "MANU", "CRUS", "TRAN", "QSTB", "QHOV",
+                    "QLOT", "QLND", "QRTL", "TKOF",
+                ],
+            ),
+            FcFirmware::Betaflight | FcFirmware::Kiss | FcFirmware::KissUltra | FcFirmware::Unknown => (
+                &[
+                    (0x01, Rssi),
+                    (0x7b, LinkQuality),
+                    (0x89, Latitude),
+                    (0x98, Longitude),
+                    (0x06, Volt),
+                    (0x9a, Amp),
+                    (0x07, MilliampHours),
+                    (0x7f, Altitude),
+                    (0x0c, Meter),
+                    (0x0f, Feet),
+                    (0x9e, KilometersPerHour),
+                    (0x9d, MilesPerHour),
+                    (0x9f, MetersPerSecond),
+                ],
+                &[
+                    "ACRO", "ANGL", "HOR", "AIR", "RESC", "HEAD", "PASS", "ATRN", "ALTH", "POSH", "MAG", "!FS!",
+                ],
+            ),
+        };
+
+        Self {
+            symbols: symbols.iter().copied().collect(),
+            flight_modes,
+        }
+    }
+
+    pub fn decode(&self, index: u16) -> OsdChar {
+        if let Some(symbol) = self.symbols.get(&index) {
+            return OsdChar::Symbol(*symbol);
+        }
+        match char::from_u32(index.into()) {
+            Some(c) if c.is_ascii_graphic() => OsdChar::Text(c),
+            _ => OsdChar::Other,
+        }
+    }
+
+    pub fn is_flight_mode(&self, word: &str) -> bool {
+        self.flight_modes.contains(&word)
+    }
+}
@@ -0,0 +1,299 @@
+//! Read flight data back from the OSD, by recognising the elements the flight controller draws: a value with the
+//! symbol of the element in front of it, like RSSI and GPS coordinates, or with the unit after it, like the battery
+//! voltage.
+
+mod charmap;
+
+use std::collections::BTreeMap;
+
+pub use charmap::{CharMap, OsdChar, Symbol};
+use serde::Serialize;
+
+use super::{frame::Frame, osd_file::OsdFile};
+
+const FEET_TO_METERS: f32 = 0.3048;
+const MPH_TO_KMH: f32 = 1.609_344;
+const MPS_TO_KMH: f32 = 3.6;
+
+/// Values shown on the OSD at `time_millis`. Elements that aren't on screen, or aren't recognised, are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Telemetry {
+    pub time_millis: u32,
+    pub battery_voltage: Option<f32>,
+    pub current: Option<f32>,
+    pub consumed_mah: Option<u32>,
+    pub altitude_m: Option<f32>,
+    pub speed_kmh: Option<f32>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub flight_mode: Option<String>,
+    pub rssi: Option<u32>,
+    pub link_quality: Option<u32>,
+    pub armed: Option<bool>,
+}
+
+impl Telemetry {
+    /// The armed state is only known in frames that show the `ARMED` or `DISARMED` message, see
+    /// [`OsdFile::telemetry`] for the state of the frames in between.
+    pub fn from_frame(frame: &Frame, charmap: &CharMap) -> Self {
+        let mut telemetry = Self {
+            time_millis: frame.time_millis,
+            ..Default::default()
+        };
+        for row in rows(frame, charmap).values() {
+            telemetry.read_values(row);
+            telemetry.read_words(row, charmap);
+        }
+        telemetry
+    }
+
+    fn read_values(&mut self, row: &[OsdChar]) {
+        let mut i = 0;
+        while i < row.len() {
+            if !starts_number(&row[i..]) {
+                i += 1;
+                continue;
+            }
+            let end = row[i..]
+                .iter()
+                .position(|c| !matches!(c, OsdChar::Text('0'..='9' | '.' | '-' | ':')))
+                .map_or(row.len(), |len| i + len);
+            let number = row[i..end]
+                .iter()
+                .map(|c| match c {
+                    OsdChar::Text(c) => *c,
+                    _ => unreachable!(),
+                })
+                .collect::<String>();
+            let prefix = row[..i].iter().rev().find(|c| !c.is_text(' '));
+            let suffix = row.get(end);
+            self.read_value(&number, prefix, suffix);
+            i = end;
+        }
+    }
+
+    fn read_value(&mut self, number: &str, prefix: Option<&OsdChar>, suffix: Option<&OsdChar>) {
+        use Symbol::*;
+        // A value only belongs to the symbol in front of it if it has no unit
+        let prefix = match suffix {
+            Some(OsdChar::Symbol(Meter | Feet)) | None | Some(OsdChar::Text(_) | OsdChar::Other) => prefix,
+            Some(OsdChar::Symbol(_)) => None,
+        };
+
+        // Link quality can be shown as `RF mode:LQ`
+        let value = if matches!(prefix, Some(OsdChar::Symbol(LinkQuality))) {
+            number.rsplit(':').next().unwrap_or(number)
+        } else if number.contains(':') {
+            return;
+        } else {
+            number
+        };
+        let Ok(float) = value.parse::<f64>() else {
+            return;
+        };
+        let float32 = float as f32;
+
+        match (prefix, suffix) {
+            (_, Some(OsdChar::Symbol(Volt))) => set(&mut self.battery_voltage, float32),
+            (_, Some(OsdChar::Symbol(Amp))) => set(&mut self.current, float32),
+            (_, Some(OsdChar::Symbol(MilliampHours))) => set(&mut self.consumed_mah, float.max(0.0) as u32),
+            (_, Some(OsdChar::Symbol(AltitudeMeter)))
+            | (Some(OsdChar::Symbol(Altitude)), Some(OsdChar::Symbol(Meter))) => set(&mut self.altitude_m, float32),
+            (_, Some(OsdChar::Symbol(AltitudeFeet)))
+            | (Some(OsdChar::Symbol(Altitude)), Some(OsdChar::Symbol(Feet))) => {
+                set(&mut self.altitude_m, float32 * FEET_TO_METERS)
+            }
+            (_, Some(OsdChar::Symbol(KilometersPerHour))) => set(&mut self.speed_kmh, float32),
+            (_, Some(OsdChar::Symbol(MilesPerHour))) => set(&mut self.speed_kmh, float32 * MPH_TO_KMH),
+            (_, Some(OsdChar::Symbol(MetersPerSecond))) => set(&mut self.speed_kmh, float32 * MPS_TO_KMH),
+            (Some(OsdChar::Symbol(Rssi)), _) => set(&mut self.rssi, float.max(0.0) as u32),
+            (Some(OsdChar::Symbol(LinkQuality)), _) => set(&mut self.link_quality, float.max(0.0) as u32),
+            (Some(OsdChar::Symbol(Latitude)), _) => set(&mut self.latitude, float),
+            (Some(OsdChar::Symbol(Longitude)), _) => set(&mut self.longitude, float),
+            _ => {}
+        }
+    }
+
+    fn read_words(&mut self, row: &[OsdChar], charmap: &CharMap) {
+        for word in words(row) {
+            match word.as_str() {
+                "DISARMED" => self.armed = Some(false),
+                "ARMED" => set(&mut self.armed, true),
+                word if charmap.is_flight_mode(word) => set(&mut self.flight_mode, word.to_string()),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl OsdFile {
+    /// Telemetry of every frame, read with the character map of the font. Flight controllers only show the armed state
+    /// for a moment, so a frame without the message keeps the state of the last frame that had one.
+    pub fn telemetry(&self) -> Vec<Telemetry> {
+        let charmap = CharMap::for_firmware(&self.fc_firmware);
+        let mut armed = None;
+        self.frames
+            .iter()
+            .map(|frame| {
+                let mut telemetry = Telemetry::from_frame(frame, &charmap);
+                armed = telemetry.armed.or(armed);
+                telemetry.armed = armed;
+                telemetry
+            })
+            .collect()
+    }
+}
+
+/// The characters of each row of the frame, with spaces in the cells that are empty.
+pub fn rows(frame: &Frame, charmap: &CharMap) -> BTreeMap<u32, Vec<OsdChar>> {
+    let mut rows = BTreeMap::<u32, Vec<OsdChar>>::new();
+    for glyph in &frame.glyphs {
+        let row = rows.entry(glyph.grid_position.y).or_default();
+        let x = glyph.grid_position.x as usize;
+        if row.len() <= x {
+            row.resize(x + 1, OsdChar::Text(' '));
+        }
+        row[x] = charmap.decode(glyph.index);
+    }
+    rows
+}
+
+/// Runs of letters, digits and punctuation, split by spaces and symbols.
+fn words(row: &[OsdChar]) -> impl Iterator<Item = String> + '_ {
+    row.split(|c| !matches!(c, OsdChar::Text(c) if *c != ' '))
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            word.iter()
+                .filter_map(|c| match c {
+                    OsdChar::Text(c) => Some(*c),
+                    _ => None,
+                })
+                .collect()
+        })
+}
+
+/// A digit, or a sign or decimal point in front of one.
+fn starts_number(chars: &[OsdChar]) -> bool {
+    matches!(
+        chars,
+        [OsdChar::Text('0'..='9'), ..] | [OsdChar::Text('-' | '.'), OsdChar::Text('0'..='9'), ..]
+    )
+}
+
+/// Keep the first value when an element shows up more than once, like the voltage of the battery and the average cell
+/// voltage.
+fn set<T>(field: &mut Option<T>, value: T) {
+    field.get_or_insert(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osd::{
+        glyph::{Glyph, GridPosition},
+        FcFirmware, GridSize,
+    };
+
+    enum Cell<'a> {
+        Text(&'a str),
+        Index(u16),
+    }
+
+    fn frame(time_millis: u32, rows: &[(u32, u32, &[Cell])]) -> Frame {
+        let mut glyphs = vec![];
+        for &(x, y, cells) in rows {
+            let indices = cells.iter().flat_map(|cell| match cell {
+                Cell::Text(text) => text.bytes().map(u16::from).collect::<Vec<_>>(),
+                Cell::Index(index) => vec![*index],
+            });
+            for (i, index) in indices.enumerate() {
+                if index != 0x20 {
+                    glyphs.push(Glyph {
+                        index,
+                        grid_position: GridPosition { x: x + i as u32, y },
+                    });
+                }
+            }
+        }
+        Frame { time_millis, glyphs }
+    }
+
+    #[test]
+    fn betaflight_elements() {
+        use Cell::*;
+        let charmap = CharMap::for_firmware(&FcFirmware::Betaflight);
+        let frame = frame(
+            1500,
+            &[
+                (1, 0, &[Index(0x01), Text(" 87")]),
+                (10, 0, &[Index(0x7b), Text("2:100")]),
+                (40, 0, &[Index(0x90), Text("16.24"), Index(0x06)]),
+                (1, 1, &[Text("ANGL")]),
+                (40, 1, &[Text(" 12.40"), Index(0x9a)]),
+                (40, 2, &[Text(" 850"), Index(0x07)]),
+                (1, 3, &[Index(0x7f), Text("-1.5"), Index(0x0f)]),
+                (1, 4, &[Index(0x70), Text(" 30"), Index(0x9d)]),
+                (1, 5, &[Index(0x89), Text("47.3977419")]),
+                (20, 5, &[Index(0x98), Text("-8.5455938")]),
+                (20, 10, &[Text("DISARMED")]),
+            ],
+        );
+
+        let telemetry = Telemetry::from_frame(&frame, &charmap);
+        assert_eq!(telemetry.time_millis, 1500);
+        assert_eq!(telemetry.rssi, Some(87));
+        assert_eq!(telemetry.link_quality, Some(100));
+        assert_eq!(telemetry.battery_voltage, Some(16.24));
+        assert_eq!(telemetry.current, Some(12.4));
+        assert_eq!(telemetry.consumed_mah, Some(850));
+        assert_eq!(telemetry.altitude_m, Some(-1.5 * FEET_TO_METERS));
+        assert_eq!(telemetry.speed_kmh, Some(30.0 * MPH_TO_KMH));
+        assert_eq!(telemetry.latitude, Some(47.3977419));
+        assert_eq!(telemetry.longitude, Some(-8.5455938));
+        assert_eq!(telemetry.armed, Some(false));
+    }
+
+    #[test]
+    fn inav_and_ardupilot_symbols() {
+        use Cell::*;
+        let inav = frame(
+            0,
+            &[(1, 0, &[Text("123"), Index(0x76)]), (1, 1, &[Text("4.2"), Index(0x1f)])],
+        );
+        let telemetry = Telemetry::from_frame(&inav, &CharMap::for_firmware(&FcFirmware::Inav));
+        assert_eq!(telemetry.altitude_m, Some(123.0));
+        assert_eq!(telemetry.battery_voltage, Some(4.2));
+
+        let ardupilot = frame(
+            0,
+            &[(1, 0, &[Index(0xa6), Text("12.5")]), (1, 1, &[Text("10"), Index(0x9f)])],
+        );
+        let telemetry = Telemetry::from_frame(&ardupilot, &CharMap::for_firmware(&FcFirmware::ArduPilot));
+        assert_eq!(telemetry.latitude, Some(12.5));
+        assert_eq!(telemetry.speed_kmh, Some(36.0));
+    }
+
+    #[test]
+    fn flight_mode_and_armed_state_over_the_file() {
+        use Cell::*;
+        let frames = vec![
+            frame(0, &[(1, 1, &[Text("DISARMED")]), (1, 2, &[Text("ACRO")])]),
+            frame(100, &[(1, 1, &[Text("ARMED")]), (1, 2, &[Text("ACRO")])]),
+            frame(200, &[(1, 2, &[Text("HOR")])]),
+        ];
+        let osd_file = OsdFile::from_frames(
+            Default::default(),
+            "Walksnail",
+            FcFirmware::Betaflight,
+            GridSize::HD,
+            frames,
+        );
+
+        let telemetry = osd_file.telemetry();
+        assert_eq!(
+            telemetry.iter().map(|t| t.armed).collect::<Vec<_>>(),
+            [Some(false), Some(true), Some(true)]
+        );
+        assert_eq!(telemetry[2].flight_mode.as_deref(), Some("HOR"));
+    }
+}
@@ -0,0 +1,210 @@
+//! Masks that only hide OSD elements part of the time, like the pre-arm warnings or the statistics screen after
+//! landing.
+
+use std::{collections::HashSet, fmt::Display, ops::RangeInclusive};
+
+use serde::{Deserialize, Serialize};
+
+use super::{frame::Frame, glyph::Glyph};
+use crate::util::Coordinates;
+
+/// What is known about the flight at a video frame, for deciding which timed masks are active.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FlightState {
+    /// Time in the video.
+    pub time_secs: f32,
+    pub armed: bool,
+    /// Whether the craft was armed at any earlier point in the recording.
+    pub has_been_armed: bool,
+}
+
+impl FlightState {
+    /// Follow the armed state through the OSD frames. Flight controllers only show the `ARMED` message for a moment and
+    /// `DISARMED` or the statistics screen after disarming, so the state is kept until the other message shows up.
+    pub fn update(&mut self, frame: &Frame) {
+        if let Some(armed) = armed_message(frame) {
+            self.armed = armed;
+            self.has_been_armed |= armed;
+        }
+    }
+
+    /// The state at the last of `frames`, e.g. for the preview of a single frame.
+    pub fn after_frames<'a>(frames: impl IntoIterator<Item = &'a Frame>, time_secs: f32) -> Self {
+        let mut state = Self {
+            time_secs,
+            ..Default::default()
+        };
+        for frame in frames {
+            state.update(frame);
+        }
+        state
+    }
+}
+
+/// The armed state shown by the frame, if it shows the `ARMED` or `DISARMED` message. Betaflight doesn't show `DISARMED`
+/// but switches to its `--- STATS ---` screen, which counts as disarmed too. These messages are text, which is at the
+/// ASCII positions in all fonts, so only the text glyphs are looked at and the firmware of the OSD doesn't matter. This
+/// runs for every OSD frame of a render, so it doesn't read the rest of the telemetry.
+fn armed_message(frame: &Frame) -> Option<bool> {
+    let mut text = frame
+        .glyphs
+        .iter()
+        .filter(|glyph| (0x21..=0x7e).contains(&glyph.index))
+        .map(|glyph| (glyph.grid_position.y, glyph.grid_position.x, glyph.index as u8))
+        .collect::<Vec<_>>();
+    text.sort_unstable();
+
+    // Words are runs of text glyphs next to each other on a row
+    let mut armed = None;
+    for word in text.chunk_by(|(y, x, _), (next_y, next_x, _)| y == next_y && x.checked_add(1) == Some(*next_x)) {
+        match word.iter().map(|(_, _, c)| *c).collect::<Vec<_>>().as_slice() {
+            b"DISARMED" | b"STATS" => return Some(false),
+            b"ARMED" => armed = Some(true),
+            _ => {}
+        }
+    }
+    armed
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum MaskTrigger {
+    /// Before the craft is armed for the first time, e.g. for the pre-arm warnings. Recordings that start after arming
+    /// don't show the `ARMED` message so these masks stay active the whole video.
+    #[default]
+    UntilArmed,
+    /// While the craft is disarmed after a flight, e.g. for the post-flight statistics. Needs the `DISARMED` message or
+    /// the Betaflight statistics screen on the OSD, firmware that shows neither doesn't trigger these masks.
+    AfterDisarm,
+    /// Between two times in the video, in seconds.
+    Time { start_secs: f32, end_secs: f32 },
+}
+
+impl MaskTrigger {
+    pub fn is_active(&self, state: &FlightState) -> bool {
+        match self {
+            Self::UntilArmed => !state.has_been_armed,
+            Self::AfterDisarm => state.has_been_armed && !state.armed,
+            Self::Time { start_secs, end_secs } => (*start_secs..*end_secs).contains(&state.time_secs),
+        }
+    }
+}
+
+impl Display for MaskTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UntilArmed => write!(f, "until armed"),
+            Self::AfterDisarm => write!(f, "after disarm"),
+            Self::Time { start_secs, end_secs } => write!(f, "from {start_secs:.1}s to {end_secs:.1}s"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MaskTarget {
+    /// The whole OSD.
+    All,
+    Positions(HashSet<Coordinates<u32>>),
+    Glyphs(RangeInclusive<u16>),
+}
+
+impl MaskTarget {
+    pub fn contains(&self, glyph: &Glyph) -> bool {
+        match self {
+            Self::All => true,
+            Self::Positions(positions) => positions.contains(&glyph.grid_position),
+            Self::Glyphs(range) => range.contains(&glyph.index),
+        }
+    }
+}
+
+impl Display for MaskTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::All => write!(f, "Whole OSD"),
+            Self::Positions(positions) => write!(f, "{} positions", positions.len()),
+            Self::Glyphs(range) if range.start() == range.end() => write!(f, "Symbol 0x{:02X}", range.start()),
+            Self::Glyphs(range) => write!(f, "Symbols 0x{:02X}-0x{:02X}", range.start(), range.end()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimedMask {
+    pub target: MaskTarget,
+    pub trigger: MaskTrigger,
+}
+
+impl TimedMask {
+    pub fn is_masked(&self, glyph: &Glyph, state: &FlightState) -> bool {
+        self.trigger.is_active(state) && self.target.contains(glyph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osd::glyph::GridPosition;
+
+    fn text_frame(text: &str) -> Frame {
+        let glyphs = text
+            .bytes()
+            .enumerate()
+            .map(|(x, b)| Glyph {
+                index: b.into(),
+                grid_position: GridPosition { x: x as u32, y: 0 },
+            })
+            .collect();
+        Frame { time_millis: 0, glyphs }
+    }
+
+    #[test]
+    fn arming_triggers() {
+        let until_armed = MaskTrigger::UntilArmed;
+        let after_disarm = MaskTrigger::AfterDisarm;
+        let mut state = FlightState::default();
+
+        state.update(&text_frame("DISARMED"));
+        assert!(until_armed.is_active(&state));
+        assert!(!after_disarm.is_active(&state));
+
+        state.update(&text_frame("ARMED ACRO"));
+        state.update(&text_frame("ACRO"));
+        assert!(!until_armed.is_active(&state));
+        assert!(!after_disarm.is_active(&state));
+
+        state.update(&text_frame("DISARMED"));
+        assert!(!until_armed.is_active(&state));
+        assert!(after_disarm.is_active(&state));
+    }
+
+    #[test]
+    fn betaflight_statistics_screen_is_disarmed() {
+        let mut state = FlightState::default();
+        state.update(&text_frame("ARMED"));
+        state.update(&text_frame("--- STATS ---"));
+        assert!(MaskTrigger::AfterDisarm.is_active(&state));
+    }
+
+    #[test]
+    fn time_trigger() {
+        let mask = TimedMask {
+            target: MaskTarget::Glyphs(0x41..=0x5a),
+            trigger: MaskTrigger::Time {
+                start_secs: 10.0,
+                end_secs: 20.0,
+            },
+        };
+        let glyph = Glyph {
+            index: 0x41,
+            grid_position: GridPosition { x: 0, y: 0 },
+        };
+        let at = |time_secs| FlightState {
+            time_secs,
+            ..Default::default()
+        };
+
+        assert!(!mask.is_masked(&glyph, &at(9.9)));
+        assert!(mask.is_masked(&glyph, &at(10.0)));
+        assert!(!mask.is_masked(&glyph, &at(20.0)));
+    }
+}
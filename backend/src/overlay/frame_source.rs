@@ -0,0 +1,92 @@
+use ffmpeg_sidecar::{child::FfmpegChild, event::OutputVideoFrame, iter::FfmpegIterator};
+
+use crate::ffmpeg::VideoInfo;
+
+/// Where the frames that the OSD is drawn on come from.
+pub enum FrameSource {
+    /// Frames decoded from the input video by ffmpeg.
+    Decoder {
+        decoder_iter: FfmpegIterator,
+        decoder_process: FfmpegChild,
+    },
+    /// Empty frames generated at the frame rate of the output. Used when the input video isn't shown in the output
+    /// (chroma key and transparent renders) or there is no input video at all, which avoids decoding the whole video
+    /// only to throw away the pixels.
+    Generated(GeneratedFrames),
+}
+
+/// Iterator over frames without image data, timed like the frames of `video_info` would be.
+pub struct GeneratedFrames {
+    width: u32,
+    height: u32,
+    frame_rate: f32,
+    frame_count: u32,
+    next_frame: u32,
+}
+
+impl GeneratedFrames {
+    pub fn new(video_info: &VideoInfo, frame_count: u32) -> Self {
+        Self {
+            width: video_info.width,
+            height: video_info.height,
+            frame_rate: video_info.frame_rate,
+            frame_count,
+            next_frame: 0,
+        }
+    }
+
+    /// Stop generating frames.
+    pub fn abort(&mut self) {
+        self.frame_count = self.next_frame;
+    }
+}
+
+impl Iterator for GeneratedFrames {
+    type Item = OutputVideoFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_frame >= self.frame_count {
+            return None;
+        }
+
+        let frame_num = self.next_frame;
+        self.next_frame += 1;
+        Some(OutputVideoFrame {
+            width: self.width,
+            height: self.height,
+            pix_fmt: "rgba".into(),
+            output_index: 0,
+            data: Vec::new(),
+            frame_num,
+            timestamp: frame_num as f32 / self.frame_rate,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn generate_frames_at_frame_rate() {
+        let video_info = VideoInfo::synthetic(1920, 1080, 60.0, Duration::from_secs(2));
+        let frames = GeneratedFrames::new(&video_info, video_info.total_frames).collect::<Vec<_>>();
+
+        assert_eq!(frames.len(), 120);
+        assert_eq!(frames[0].timestamp, 0.0);
+        assert_eq!(frames[60].timestamp, 1.0);
+        assert!(frames.iter().all(|f| f.width == 1920 && f.height == 1080));
+    }
+
+    #[test]
+    fn stop_generating_after_abort() {
+        let video_info = VideoInfo::synthetic(1280, 720, 30.0, Duration::from_secs(10));
+        let mut frames = GeneratedFrames::new(&video_info, video_info.total_frames);
+
+        frames.next();
+        frames.abort();
+        assert!(frames.next().is_none());
+    }
+}
@@ -1,76 +1,82 @@
 use std::{collections::HashMap, iter::Peekable, vec::IntoIter};
 
 use crossbeam_channel::{Receiver, Sender};
-use ffmpeg_sidecar::{
-    child::FfmpegChild,
-    event::{FfmpegEvent, OutputVideoFrame},
-    iter::FfmpegIterator,
-};
+use ffmpeg_sidecar::event::{FfmpegEvent, OutputVideoFrame};
 use image::{Rgba, RgbaImage};
 
-use super::{overlay_osd_cached, overlay_srt_data};
+use super::{overlay_osd_cached, overlay_srt_data, FrameSource};
 use crate::{
     ffmpeg::{handle_decoder_events, FromFfmpegMessage, ToFfmpegMessage},
     font,
-    osd::{self, OsdOptions},
+    osd::{self, FlightState, GridSize, OsdOptions},
     srt::{self, SrtOptions},
 };
 
 pub struct FrameOverlayIter<'a> {
-    decoder_iter: FfmpegIterator,
-    decoder_process: FfmpegChild,
+    frame_source: FrameSource,
     osd_frames_iter: Peekable<IntoIter<osd::Frame>>,
+    osd_grid_size: GridSize,
     srt_frames_iter: Peekable<IntoIter<srt::SrtFrame>>,
-    font_file: font::FontFile,
+    font_file: Option<font::FontFile>,
     osd_options: OsdOptions,
     srt_options: SrtOptions,
     srt_font: rusttype::Font<'a>,
-    current_osd_frame: osd::Frame,
+    current_osd_frame: Option<osd::Frame>,
+    /// Armed state up to the current OSD frame, for the timed masks.
+    flight_state: FlightState,
     current_srt_frame: Option<srt::SrtFrame>,
     ffmpeg_sender: Sender<FromFfmpegMessage>,
     ffmpeg_receiver: Receiver<ToFfmpegMessage>,
-    chroma_key: Option<Rgba<u8>>,
+    background_color: Option<Rgba<u8>>,
     pad_4_3_to_16_9: bool,
+    time_offset_secs: f32,
     glyph_cache: HashMap<u16, RgbaImage>,
 }
 
 impl<'a> FrameOverlayIter<'a> {
-    #[tracing::instrument(skip(decoder_iter, decoder_process, osd_frames, font_file), level = "debug")]
+    /// The OSD is only drawn when there are OSD frames and a font, so SRT data can be rendered on its own.
+    /// `background_color` is required for generated frames because they don't have any image data.
+    #[tracing::instrument(skip(frame_source, osd_frames, font_file), level = "debug")]
     pub fn new(
-        decoder_iter: FfmpegIterator,
-        decoder_process: FfmpegChild,
+        frame_source: FrameSource,
         osd_frames: Vec<osd::Frame>,
+        osd_grid_size: GridSize,
         srt_frames: Vec<srt::SrtFrame>,
-        font_file: font::FontFile,
+        font_file: Option<font::FontFile>,
         srt_font: rusttype::Font<'a>,
         osd_options: &OsdOptions,
         srt_options: &SrtOptions,
         ffmpeg_sender: Sender<FromFfmpegMessage>,
         ffmpeg_receiver: Receiver<ToFfmpegMessage>,
-        chroma_key: Option<[f32; 3]>,
+        background_color: Option<Rgba<u8>>,
         pad_4_3_to_16_9: bool,
+        time_offset_secs: f32,
     ) -> Self {
         let mut osd_frames_iter = osd_frames.into_iter();
         let mut srt_frames_iter = srt_frames.into_iter();
-        let first_osd_frame = osd_frames_iter.next().unwrap();
+        let first_osd_frame = osd_frames_iter.next();
         let first_srt_frame = srt_frames_iter.next();
-        let chroma_key =
-            chroma_key.map(|c| Rgba([(c[0] * 255.0) as u8, (c[1] * 255.0) as u8, (c[2] * 255.0) as u8, 255]));
+        let mut flight_state = FlightState::default();
+        if let Some(first_osd_frame) = &first_osd_frame {
+            flight_state.update(first_osd_frame);
+        }
         Self {
-            decoder_iter,
-            decoder_process,
+            frame_source,
             osd_frames_iter: osd_frames_iter.peekable(),
+            osd_grid_size,
             srt_frames_iter: srt_frames_iter.peekable(),
             font_file,
             osd_options: osd_options.clone(),
             srt_options: srt_options.clone(),
             srt_font: srt_font.clone(),
             current_osd_frame: first_osd_frame,
+            flight_state,
             current_srt_frame: first_srt_frame,
             ffmpeg_sender,
             ffmpeg_receiver,
-            chroma_key,
+            background_color,
             pad_4_3_to_16_9,
+            time_offset_secs,
             glyph_cache: HashMap::new(),
         }
     }
@@ -82,74 +88,111 @@ impl Iterator for FrameOverlayIter<'_> {
     fn next(&mut self) -> Option<Self::Item> {
         //  On every iteration check if the render should be stopped
         while let Ok(ToFfmpegMessage::AbortRender) = self.ffmpeg_receiver.try_recv() {
-            self.decoder_process.quit().unwrap();
+            match &mut self.frame_source {
+                FrameSource::Decoder { decoder_process, .. } => decoder_process.quit().unwrap(),
+                FrameSource::Generated(generated_frames) => generated_frames.abort(),
+            }
         }
 
-        self.decoder_iter.find_map(|e| match e {
-            FfmpegEvent::OutputFrame(mut video_frame) => {
-                // For every video frame check if frame time is later than the next OSD frame time.
-                // If so advance the iterator over the OSD frames so we use the correct OSD frame
-                // for this video frame
-                if let Some(next_osd_frame) = self.osd_frames_iter.peek() {
-                    let next_osd_frame_secs = next_osd_frame.time_millis as f32 / 1000.0;
-                    if video_frame.timestamp > next_osd_frame_secs * self.osd_options.osd_playback_speed_factor {
-                        self.current_osd_frame = self.osd_frames_iter.next().unwrap();
-                    }
+        let video_frame = match &mut self.frame_source {
+            FrameSource::Decoder { decoder_iter, .. } => decoder_iter.find_map(|e| match e {
+                FfmpegEvent::OutputFrame(video_frame) => Some(video_frame),
+                other_event => {
+                    handle_decoder_events(other_event, &self.ffmpeg_sender);
+                    None
                 }
-
-                if let Some(next_srt_frame) = self.srt_frames_iter.peek() {
-                    let next_srt_start_time_secs = next_srt_frame.start_time_secs;
-                    if video_frame.timestamp > next_srt_start_time_secs {
-                        self.current_srt_frame = self.srt_frames_iter.next();
-                    }
+            }),
+            FrameSource::Generated(generated_frames) => {
+                let video_frame = generated_frames.next();
+                if video_frame.is_none() {
+                    // There is no decoder to report this so do it here to let the UI know all frames were rendered
+                    self.ffmpeg_sender.send(FromFfmpegMessage::DecoderFinished).ok();
                 }
+                video_frame
+            }
+        }?;
 
-                let mut frame_image = if let Some(chroma_key) = self.chroma_key {
-                    RgbaImage::from_pixel(video_frame.width, video_frame.height, chroma_key)
-                } else {
-                    RgbaImage::from_raw(video_frame.width, video_frame.height, video_frame.data).unwrap()
-                };
-
-                // Internal letterboxing
-                let is_4_3 = (video_frame.width as f32 / video_frame.height as f32) < 1.5;
-                let mut x_offset = 0;
-                if self.pad_4_3_to_16_9 && is_4_3 {
-                    let final_width = video_frame.height * 16 / 9;
-                    let mut padded_image = RgbaImage::from_pixel(final_width, video_frame.height, Rgba([0, 0, 0, 255]));
-                    x_offset = (final_width - video_frame.width) / 2;
-                    image::imageops::overlay(&mut padded_image, &frame_image, x_offset as i64, 0);
-                    frame_image = padded_image;
-                    video_frame.width = final_width;
-                }
+        Some(self.overlay_frame(video_frame))
+    }
+}
 
-                overlay_osd_cached(
-                    &mut frame_image,
-                    &self.current_osd_frame,
-                    &self.font_file,
-                    &self.osd_options,
-                    (x_offset as i32, 0),
-                    &mut self.glyph_cache,
-                );
+impl FrameOverlayIter<'_> {
+    fn overlay_frame(&mut self, mut video_frame: OutputVideoFrame) -> OutputVideoFrame {
+        // Decoded frame timestamps start at zero when only part of the video is rendered so shift them
+        // back to the time in the full video, which is what the OSD and SRT timestamps are relative to
+        let frame_time_secs = video_frame.timestamp + self.time_offset_secs;
 
-                if let Some(current_srt_frame) = &self.current_srt_frame {
-                    if let Some(srt_data) = &current_srt_frame.data {
-                        overlay_srt_data(
-                            &mut frame_image,
-                            srt_data,
-                            &self.srt_font,
-                            &self.srt_options,
-                            (x_offset as i32, 0),
-                        );
-                    }
-                }
+        // For every video frame check if frame time is later than the next OSD frame time.
+        // If so advance the iterator over the OSD frames so we use the correct OSD frame
+        // for this video frame
+        while let Some(next_osd_frame) = self.osd_frames_iter.peek() {
+            let next_osd_frame_secs = next_osd_frame.time_millis as f32 / 1000.0;
+            if frame_time_secs <= next_osd_frame_secs * self.osd_options.osd_playback_speed_factor {
+                break;
+            }
+            self.current_osd_frame = self.osd_frames_iter.next();
+            if let Some(current_osd_frame) = &self.current_osd_frame {
+                self.flight_state.update(current_osd_frame);
+            }
+        }
+        self.flight_state.time_secs = frame_time_secs;
 
-                video_frame.data = frame_image.into_raw();
-                Some(video_frame)
+        while let Some(next_srt_frame) = self.srt_frames_iter.peek() {
+            if frame_time_secs <= next_srt_frame.start_time_secs {
+                break;
             }
-            other_event => {
-                handle_decoder_events(other_event, &self.ffmpeg_sender);
-                None
+            self.current_srt_frame = self.srt_frames_iter.next();
+        }
+
+        let mut frame_image = if let Some(background_color) = self.background_color {
+            RgbaImage::from_pixel(video_frame.width, video_frame.height, background_color)
+        } else {
+            RgbaImage::from_raw(video_frame.width, video_frame.height, video_frame.data).unwrap()
+        };
+
+        // Internal letterboxing
+        let is_4_3 = (video_frame.width as f32 / video_frame.height as f32) < 1.5;
+        let mut x_offset = 0;
+        if self.pad_4_3_to_16_9 && is_4_3 {
+            let final_width = video_frame.height * 16 / 9;
+            // Keep the bars transparent when rendering an OSD-only overlay with alpha
+            let bar_color = match self.background_color {
+                Some(color) if color[3] == 0 => color,
+                _ => Rgba([0, 0, 0, 255]),
+            };
+            let mut padded_image = RgbaImage::from_pixel(final_width, video_frame.height, bar_color);
+            x_offset = (final_width - video_frame.width) / 2;
+            image::imageops::overlay(&mut padded_image, &frame_image, x_offset as i64, 0);
+            frame_image = padded_image;
+            video_frame.width = final_width;
+        }
+
+        if let (Some(current_osd_frame), Some(font_file)) = (&self.current_osd_frame, &self.font_file) {
+            overlay_osd_cached(
+                &mut frame_image,
+                current_osd_frame,
+                &self.osd_grid_size,
+                font_file,
+                &self.osd_options,
+                &self.flight_state,
+                (x_offset as i32, 0),
+                &mut self.glyph_cache,
+            );
+        }
+
+        if let Some(current_srt_frame) = &self.current_srt_frame {
+            if let Some(srt_data) = &current_srt_frame.data {
+                overlay_srt_data(
+                    &mut frame_image,
+                    srt_data,
+                    &self.srt_font,
+                    &self.srt_options,
+                    (x_offset as i32, 0),
+                );
             }
-        })
+        }
+
+        video_frame.data = frame_image.into_raw();
+        video_frame
     }
 }
@@ -1,7 +1,9 @@
+mod frame_source;
 mod iter;
 mod osd;
 mod srt;
 
+pub use frame_source::{FrameSource, GeneratedFrames};
 pub use iter::FrameOverlayIter;
-pub use osd::{get_character_size, overlay_osd, overlay_osd_cached};
+pub use osd::{get_character_size, overlay_osd, overlay_osd_cached, scaled_character_size};
 pub use srt::overlay_srt_data;
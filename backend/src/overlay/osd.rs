@@ -7,7 +7,7 @@ use image::{
 
 use crate::{
     font::{self, CharacterSize},
-    osd::{self, OsdOptions},
+    osd::{self, FlightState, GridSize, OsdOptions},
 };
 
 pub fn get_character_size(width: u32, height: u32) -> CharacterSize {
@@ -34,6 +34,21 @@ pub fn get_character_size(width: u32, height: u32) -> CharacterSize {
     }
 }
 
+/// Size of the characters on the video. Grids with another size than the HD grid are stretched to cover the same area,
+/// like the goggles show them.
+pub fn scaled_character_size(
+    base_character_size: &CharacterSize,
+    grid_size: &GridSize,
+    osd_options: &OsdOptions,
+) -> (u32, u32) {
+    let scale_factor = osd_options.scale / 100.0;
+    let (grid_scale_x, grid_scale_y) = grid_size.character_scale();
+    (
+        (base_character_size.width() as f32 * scale_factor * grid_scale_x).round() as u32,
+        (base_character_size.height() as f32 * scale_factor * grid_scale_y).round() as u32,
+    )
+}
+
 /// Compute the scaled glyph for a given character index, or return None if the
 /// character doesn't exist in the font.
 #[inline]
@@ -54,22 +69,23 @@ fn get_scaled_glyph(
 }
 
 /// Overlay OSD glyphs onto a frame image (single-use, no caching).
-/// Used by the OSD preview path where only a single frame is rendered.
+/// Used by the OSD preview path where only a single frame is rendered. `flight_state` decides which timed masks apply.
 #[inline]
 pub fn overlay_osd(
     image: &mut RgbaImage,
     osd_frame: &osd::Frame,
+    grid_size: &GridSize,
     font: &font::FontFile,
     osd_options: &OsdOptions,
+    flight_state: &FlightState,
     offset: (i32, i32),
 ) {
     let base_character_size = get_character_size(image.width(), image.height());
-    let scale_factor = osd_options.scale / 100.0;
-    let scaled_width = (base_character_size.width() as f32 * scale_factor).round() as u32;
-    let scaled_height = (base_character_size.height() as f32 * scale_factor).round() as u32;
+    let (scaled_width, scaled_height) = scaled_character_size(&base_character_size, grid_size, osd_options);
 
+    let osd_frame = osd_frame.redact(&osd_options.redaction_rules);
     for character in &osd_frame.glyphs {
-        if character.index == 0 || osd_options.get_mask(&character.grid_position) {
+        if character.index == 0 || osd_options.is_masked(character, flight_state) {
             continue;
         }
         if let Some(scaled_image) =
@@ -92,18 +108,19 @@ pub fn overlay_osd(
 pub fn overlay_osd_cached(
     image: &mut RgbaImage,
     osd_frame: &osd::Frame,
+    grid_size: &GridSize,
     font: &font::FontFile,
     osd_options: &OsdOptions,
+    flight_state: &FlightState,
     offset: (i32, i32),
     glyph_cache: &mut HashMap<u16, RgbaImage>,
 ) {
     let base_character_size = get_character_size(image.width(), image.height());
-    let scale_factor = osd_options.scale / 100.0;
-    let scaled_width = (base_character_size.width() as f32 * scale_factor).round() as u32;
-    let scaled_height = (base_character_size.height() as f32 * scale_factor).round() as u32;
+    let (scaled_width, scaled_height) = scaled_character_size(&base_character_size, grid_size, osd_options);
 
+    let osd_frame = osd_frame.redact(&osd_options.redaction_rules);
     for character in &osd_frame.glyphs {
-        if character.index == 0 || osd_options.get_mask(&character.grid_position) {
+        if character.index == 0 || osd_options.is_masked(character, flight_state) {
             continue;
         }
 
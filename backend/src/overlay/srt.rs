@@ -1,5 +1,5 @@
-use image::{Rgba, RgbaImage};
-use imageproc::drawing::{draw_text_mut, text_size};
+use image::{Pixel, Rgba, RgbaImage};
+use imageproc::drawing::text_size;
 
 use crate::srt::{SrtFrameData, SrtOptions};
 
@@ -155,9 +155,40 @@ pub fn overlay_srt_data(
         let y = y_start + (i as i32 * line_height) + offset.1;
 
         // Draw shadow (1px offset)
-        draw_text_mut(image, shadow_color, x + 1, y + 1, scale, font, line);
+        draw_text_blended(image, shadow_color, x + 1, y + 1, scale, font, line);
 
         // Draw main text
-        draw_text_mut(image, text_color, x, y, scale, font, line);
+        draw_text_blended(image, text_color, x, y, scale, font, line);
+    }
+}
+
+/// Same as `imageproc::drawing::draw_text_mut` but alpha blends the glyph coverage onto the image instead of
+/// interpolating all channels. The result is identical on opaque video frames but glyph edges don't turn dark on a
+/// transparent background. The alpha of `color` is ignored, like `draw_text_mut` does on opaque images.
+fn draw_text_blended(
+    image: &mut RgbaImage,
+    color: Rgba<u8>,
+    x: i32,
+    y: i32,
+    scale: rusttype::Scale,
+    font: &rusttype::Font,
+    text: &str,
+) {
+    let (image_width, image_height) = (image.width() as i32, image.height() as i32);
+    let v_metrics = font.v_metrics(scale);
+    for glyph in font.layout(text, scale, rusttype::point(0.0, v_metrics.ascent)) {
+        let Some(bounding_box) = glyph.pixel_bounding_box() else {
+            continue;
+        };
+        glyph.draw(|glyph_x, glyph_y, coverage| {
+            let pixel_x = glyph_x as i32 + bounding_box.min.x + x;
+            let pixel_y = glyph_y as i32 + bounding_box.min.y + y;
+            if (0..image_width).contains(&pixel_x) && (0..image_height).contains(&pixel_y) {
+                let alpha = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+                image
+                    .get_pixel_mut(pixel_x as u32, pixel_y as u32)
+                    .blend(&Rgba([color[0], color[1], color[2], alpha]));
+            }
+        });
     }
 }
@@ -1,4 +1,5 @@
 use parse_display::FromStr;
+use serde::Serialize;
 
 #[derive(Debug, Clone)]
 pub struct SrtFrame {
@@ -8,7 +9,7 @@ pub struct SrtFrame {
     pub debug_data: Option<SrtDebugFrameData>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct SrtFrameData {
     pub signal: Option<u8>,
     pub channel: Option<String>,
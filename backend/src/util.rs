@@ -1,8 +1,13 @@
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
 use derivative::Derivative;
 use serde::{Deserialize, Serialize};
 
+use crate::ffmpeg::OutputFormat;
+
 #[cfg(target_os = "windows")]
 pub const CREATE_NO_WINDOW: u32 = 0x08000000;
 
@@ -18,7 +23,7 @@ impl<T> Coordinates<T> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Dimension<T> {
     pub width: T,
     pub height: T,
@@ -30,6 +35,24 @@ impl Display for Dimension<u32> {
     }
 }
 
+/// Output path next to the input video. PNG sequences are written to their own directory with an ffmpeg image
+/// sequence pattern as file name.
+pub fn get_output_video_path(input_video_path: &Path, output_format: OutputFormat) -> PathBuf {
+    let input_video_file_name = input_video_path.file_stem().unwrap().to_string_lossy();
+    let mut output_video_path = input_video_path.parent().unwrap().to_path_buf();
+    if output_format == OutputFormat::PngSequence {
+        output_video_path.push(format!("{}_with_osd", input_video_file_name));
+        output_video_path.push(format!("{}_%06d.png", input_video_file_name));
+    } else {
+        output_video_path.push(format!(
+            "{}_with_osd.{}",
+            input_video_file_name,
+            output_format.extension()
+        ));
+    }
+    output_video_path
+}
+
 #[derive(Debug, Serialize, Deserialize, Derivative)]
 #[derivative(Default)]
 pub struct AppUpdate {
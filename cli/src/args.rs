@@ -0,0 +1,546 @@
+use std::{collections::HashSet, ops::RangeInclusive, path::PathBuf, time::Duration};
+
+use backend::{
+    ffmpeg::{AudioMode, OutputFormat, UpscaleTarget},
+    osd::{MaskTarget, MaskTrigger, OsdOptions, RedactionAction, RedactionPattern, RedactionRule, TimedMask},
+    srt::SrtOptions,
+    util::Coordinates,
+};
+use clap::{Args, Parser, ValueEnum};
+
+/// Render the flight controller OSD and SRT data onto a Walksnail Avatar, Ascent or Artlynk DVR recording.
+#[derive(Debug, Parser)]
+#[command(name = "walksnail-osd-cli", version)]
+pub struct Cli {
+    /// Input video file. Can be left out to render only the OSD, see --size and --fps
+    pub video: Option<PathBuf>,
+
+    /// OSD file or video with embedded OSD data. Defaults to the `.osd` file next to the video, or OSD data embedded in
+    /// the video (Artlynk)
+    #[arg(long, required_unless_present = "video")]
+    pub osd: Option<PathBuf>,
+
+    /// Fail on a truncated OSD file instead of rendering the frames that were recorded completely
+    #[arg(long)]
+    pub strict_osd: bool,
+
+    /// SRT file. Defaults to the `.srt` file next to the video, if it exists
+    #[arg(long)]
+    pub srt: Option<PathBuf>,
+
+    /// Font file. Defaults to the bundled font matching the FC firmware and video resolution
+    #[arg(long)]
+    pub font: Option<PathBuf>,
+
+    /// Output video file. Defaults to `<video>_with_osd.<ext>` next to the input video. For PNG sequences this is an
+    /// ffmpeg image sequence pattern like `frames/%06d.png`
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Path to the ffmpeg binary
+    #[arg(long, default_value = "ffmpeg")]
+    pub ffmpeg_path: PathBuf,
+
+    /// Path to the ffprobe binary
+    #[arg(long, default_value = "ffprobe")]
+    pub ffprobe_path: PathBuf,
+
+    /// Log more details about what is going on
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    #[command(flatten)]
+    pub osd_args: OsdArgs,
+
+    #[command(flatten)]
+    pub srt_args: SrtArgs,
+
+    #[command(flatten)]
+    pub render_args: RenderArgs,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "OSD options")]
+pub struct OsdArgs {
+    /// Horizontal position of the OSD in pixels from the left edge. Defaults to centering the OSD
+    #[arg(long, allow_negative_numbers = true)]
+    pub osd_x: Option<i32>,
+
+    /// Vertical position of the OSD in pixels from the top edge
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub osd_y: i32,
+
+    /// Scale of the OSD characters as a percentage
+    #[arg(long, default_value_t = 100.0)]
+    pub osd_scale: f32,
+
+    /// Do not correct for wrong OSD timestamps in <=32.37.10 firmwares
+    #[arg(long)]
+    pub no_adjust_playback_speed: bool,
+
+    /// Hide the OSD element at grid position X,Y. Can be used multiple times
+    #[arg(long = "mask", value_name = "X,Y", value_parser = parse_grid_position)]
+    pub masks: Vec<Coordinates<u32>>,
+
+    /// Hide a font character, or a range of characters, wherever it is on the OSD. Indices can be decimal or
+    /// hexadecimal like 0x89. Can be used multiple times
+    #[arg(long = "mask-glyph", value_name = "INDEX[-INDEX]", value_parser = parse_glyph_range)]
+    pub glyph_masks: Vec<RangeInclusive<u16>>,
+
+    /// Hide part of the OSD only some of the time. WHEN is `until-armed`, `after-disarm` or a time range START..END in
+    /// the input video. WHAT is `all`, a grid position X,Y or `glyph:INDEX[-INDEX]`. Can be used multiple times
+    #[arg(long = "timed-mask", value_name = "WHEN=WHAT", value_parser = parse_timed_mask)]
+    pub timed_masks: Vec<TimedMask>,
+
+    /// Hide this text wherever it shows up on the OSD, e.g. the pilot name. Can be used multiple times
+    #[arg(long = "redact", value_name = "TEXT")]
+    pub redacted_texts: Vec<String>,
+
+    /// Hide the GPS coordinates on the OSD
+    #[arg(long)]
+    pub redact_coordinates: bool,
+
+    /// Draw this character over redacted text instead of removing it
+    #[arg(long, value_name = "CHAR")]
+    pub redact_with: Option<char>,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "SRT options")]
+pub struct SrtArgs {
+    /// Horizontal position of the SRT data in % of the video width
+    #[arg(long, default_value_t = 1.5)]
+    pub srt_x: f32,
+
+    /// Vertical position of the SRT data in % of the video height
+    #[arg(long, default_value_t = 94.0)]
+    pub srt_y: f32,
+
+    /// Font size of the SRT data
+    #[arg(long, default_value_t = 34.0)]
+    pub srt_scale: f32,
+
+    /// SRT data to render. Defaults to the same selection as the GUI for the type of SRT file
+    #[arg(long, value_delimiter = ',')]
+    pub srt_fields: Option<Vec<SrtField>>,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Rendering options")]
+pub struct RenderArgs {
+    /// Output format. All formats except mp4 render only the OSD on a transparent background
+    #[arg(long, value_enum, default_value_t = Format::Mp4)]
+    pub format: Format,
+
+    /// ffmpeg encoder used for rendering mp4 output
+    #[arg(long, default_value = "libx264")]
+    pub encoder: String,
+
+    /// Target bitrate of the rendered video in Mbps. Defaults to the bitrate of the input video
+    #[arg(long)]
+    pub bitrate: Option<u32>,
+
+    /// Upscale the output video
+    #[arg(long, value_enum, default_value_t = Upscale::None)]
+    pub upscale: Upscale,
+
+    /// Add black bars on the sides to transform 4:3 video into 16:9
+    #[arg(long)]
+    pub pad_4_3_to_16_9: bool,
+
+    /// Render on a chroma key background of the given color instead of the input video
+    #[arg(long, value_name = "RRGGBB", value_parser = parse_chroma_key)]
+    pub chroma_key: Option<[f32; 3]>,
+
+    /// What to do with the audio track of the input video
+    #[arg(long, value_enum, default_value_t = Audio::Copy)]
+    pub audio: Audio,
+
+    /// Start rendering at this time in the input video, as seconds or [HH:]MM:SS[.mmm]
+    #[arg(long, value_name = "TIME", value_parser = parse_timestamp)]
+    pub start: Option<Duration>,
+
+    /// Stop rendering at this time in the input video, as seconds or [HH:]MM:SS[.mmm]
+    #[arg(long, value_name = "TIME", value_parser = parse_timestamp)]
+    pub end: Option<Duration>,
+
+    /// Frame size of the output when rendering without an input video
+    #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "1920x1080", value_parser = parse_frame_size, conflicts_with = "video")]
+    pub size: (u32, u32),
+
+    /// Frame rate of the output when rendering without an input video
+    #[arg(long, default_value_t = 60.0, conflicts_with = "video")]
+    pub fps: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Upscale {
+    None,
+    #[value(name = "1440p")]
+    P1440,
+    #[value(name = "2160p")]
+    P2160,
+}
+
+impl From<Upscale> for UpscaleTarget {
+    fn from(value: Upscale) -> Self {
+        match value {
+            Upscale::None => UpscaleTarget::None,
+            Upscale::P1440 => UpscaleTarget::P1440,
+            Upscale::P2160 => UpscaleTarget::P2160,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// H.264/H.265 video with the OSD on top of the input video
+    Mp4,
+    /// ProRes 4444 with alpha in a .mov container
+    #[value(name = "prores4444")]
+    ProRes4444,
+    /// VP9 with alpha in a .webm container
+    Vp9Alpha,
+    /// Numbered PNG images with alpha
+    Png,
+}
+
+impl From<Format> for OutputFormat {
+    fn from(value: Format) -> Self {
+        match value {
+            Format::Mp4 => OutputFormat::Mp4,
+            Format::ProRes4444 => OutputFormat::ProRes4444,
+            Format::Vp9Alpha => OutputFormat::Vp9Alpha,
+            Format::Png => OutputFormat::PngSequence,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Audio {
+    /// Copy the audio stream without re-encoding
+    Copy,
+    /// Re-encode the audio to AAC
+    Aac,
+    /// Leave the audio out of the rendered video
+    None,
+}
+
+impl From<Audio> for AudioMode {
+    fn from(value: Audio) -> Self {
+        match value {
+            Audio::Copy => AudioMode::Copy,
+            Audio::Aac => AudioMode::Aac,
+            Audio::None => AudioMode::Disabled,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SrtField {
+    Time,
+    Sbat,
+    Gbat,
+    Signal,
+    Latency,
+    Bitrate,
+    Distance,
+    Channel,
+    Hz,
+    Sp,
+    Gp,
+    AirTemp,
+    GndTemp,
+    StyMode,
+}
+
+impl OsdArgs {
+    pub fn osd_options(&self) -> OsdOptions {
+        let mut options = OsdOptions {
+            position: Coordinates::new(self.osd_x.unwrap_or_default(), self.osd_y),
+            adjust_playback_speed: !self.no_adjust_playback_speed,
+            masked_grid_positions: self.masks.iter().copied().collect::<HashSet<_>>(),
+            scale: self.osd_scale,
+            ..Default::default()
+        };
+        let action = match self.redact_with {
+            Some(c) => RedactionAction::Replace(c),
+            None => RedactionAction::Remove,
+        };
+        let patterns = self.redacted_texts.iter().cloned().map(RedactionPattern::Text);
+        let coordinates = self.redact_coordinates.then_some(RedactionPattern::Coordinates);
+        options.redaction_rules = patterns
+            .chain(coordinates)
+            .map(|pattern| RedactionRule { pattern, action })
+            .collect();
+        options.timed_masks.clone_from(&self.timed_masks);
+        for range in &self.glyph_masks {
+            if range.start() == range.end() {
+                options.masked_glyphs.insert(*range.start());
+            } else {
+                options.mask_glyph_range(range.clone());
+            }
+        }
+        options
+    }
+}
+
+impl SrtArgs {
+    /// Apply the position, size and field selection to `defaults`, which should be the GUI defaults for the type of
+    /// SRT file that is rendered.
+    pub fn srt_options(&self, defaults: SrtOptions) -> SrtOptions {
+        let mut options = SrtOptions {
+            position: Coordinates::new(self.srt_x, self.srt_y),
+            scale: self.srt_scale,
+            ..defaults
+        };
+
+        if let Some(fields) = &self.srt_fields {
+            let show = |field| fields.contains(&field);
+            options.show_time = show(SrtField::Time);
+            options.show_sbat = show(SrtField::Sbat);
+            options.show_gbat = show(SrtField::Gbat);
+            options.show_signal = show(SrtField::Signal);
+            options.show_latency = show(SrtField::Latency);
+            options.show_bitrate = show(SrtField::Bitrate);
+            options.show_distance = show(SrtField::Distance);
+            options.show_channel = show(SrtField::Channel);
+            options.show_hz = show(SrtField::Hz);
+            options.show_sp = show(SrtField::Sp);
+            options.show_gp = show(SrtField::Gp);
+            options.show_air_temp = show(SrtField::AirTemp);
+            options.show_gnd_temp = show(SrtField::GndTemp);
+            options.show_sty_mode = show(SrtField::StyMode);
+        }
+
+        options
+    }
+}
+
+fn parse_grid_position(value: &str) -> Result<Coordinates<u32>, String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| format!("expected X,Y but got `{value}`"))?;
+    let x = x.trim().parse::<u32>().map_err(|e| format!("invalid X: {e}"))?;
+    let y = y.trim().parse::<u32>().map_err(|e| format!("invalid Y: {e}"))?;
+    Ok(Coordinates::new(x, y))
+}
+
+fn parse_glyph_range(value: &str) -> Result<RangeInclusive<u16>, String> {
+    let parse_index = |index: &str| {
+        let index = index.trim();
+        let parsed = match index.strip_prefix("0x").or_else(|| index.strip_prefix("0X")) {
+            Some(hex) => u16::from_str_radix(hex, 16),
+            None => index.parse::<u16>(),
+        };
+        parsed.map_err(|e| format!("invalid glyph index `{index}`: {e}"))
+    };
+    let (start, end) = match value.split_once('-') {
+        Some((start, end)) => (parse_index(start)?, parse_index(end)?),
+        None => {
+            let index = parse_index(value)?;
+            (index, index)
+        }
+    };
+    if start > end {
+        return Err(format!("start of glyph range `{value}` is after its end"));
+    }
+    Ok(start..=end)
+}
+
+fn parse_timed_mask(value: &str) -> Result<TimedMask, String> {
+    let (when, what) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected WHEN=WHAT but got `{value}`"))?;
+    let trigger = match when.trim() {
+        "until-armed" => MaskTrigger::UntilArmed,
+        "after-disarm" => MaskTrigger::AfterDisarm,
+        range => {
+            let (start, end) = range
+                .split_once("..")
+                .ok_or_else(|| format!("expected until-armed, after-disarm or START..END but got `{range}`"))?;
+            let (start, end) = (parse_timestamp(start)?, parse_timestamp(end)?);
+            if start >= end {
+                return Err(format!("start of time range `{range}` must be before its end"));
+            }
+            MaskTrigger::Time {
+                start_secs: start.as_secs_f32(),
+                end_secs: end.as_secs_f32(),
+            }
+        }
+    };
+    let target = match what.trim() {
+        "all" => MaskTarget::All,
+        what => match what.strip_prefix("glyph:") {
+            Some(glyphs) => MaskTarget::Glyphs(parse_glyph_range(glyphs)?),
+            None => MaskTarget::Positions(HashSet::from([parse_grid_position(what)?])),
+        },
+    };
+    Ok(TimedMask { target, trigger })
+}
+
+fn parse_chroma_key(value: &str) -> Result<[f32; 3], String> {
+    let hex = value.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(format!("expected a RRGGBB color but got `{value}`"));
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .map(|c| c as f32 / 255.0)
+            .map_err(|e| format!("invalid color `{value}`: {e}"))
+    };
+    Ok([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn parse_frame_size(value: &str) -> Result<(u32, u32), String> {
+    let (width, height) = value
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT but got `{value}`"))?;
+    let width = width.trim().parse::<u32>().map_err(|e| format!("invalid width: {e}"))?;
+    let height = height
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| format!("invalid height: {e}"))?;
+    if width == 0 || height == 0 {
+        return Err(format!("frame size must not be zero but got `{value}`"));
+    }
+    Ok((width, height))
+}
+
+fn parse_timestamp(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("expected seconds or [HH:]MM:SS[.mmm] but got `{value}`");
+    let mut parts = value.rsplit(':');
+    let seconds = parts
+        .next()
+        .and_then(|s| s.trim().parse::<f64>().ok())
+        .filter(|s| s.is_finite() && *s >= 0.0)
+        .ok_or_else(invalid)?;
+    let mut total_secs = seconds;
+    for multiplier in [60.0, 3600.0] {
+        if let Some(part) = parts.next() {
+            total_secs += part.trim().parse::<u32>().map_err(|_| invalid())? as f64 * multiplier;
+        }
+    }
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs_f64(total_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_valid_grid_position() {
+        assert_eq!(parse_grid_position("12,3"), Ok(Coordinates::new(12, 3)));
+        assert_eq!(parse_grid_position(" 0, 19"), Ok(Coordinates::new(0, 19)));
+    }
+
+    #[test]
+    fn reject_invalid_grid_position() {
+        assert!(parse_grid_position("12").is_err());
+        assert!(parse_grid_position("-1,3").is_err());
+        assert!(parse_grid_position("a,b").is_err());
+    }
+
+    #[test]
+    fn parse_valid_glyph_range() {
+        assert_eq!(parse_glyph_range("137"), Ok(137..=137));
+        assert_eq!(parse_glyph_range("0x89"), Ok(0x89..=0x89));
+        assert_eq!(parse_glyph_range("0x80-0x8F"), Ok(0x80..=0x8f));
+    }
+
+    #[test]
+    fn reject_invalid_glyph_range() {
+        assert!(parse_glyph_range("0x90-0x80").is_err());
+        assert!(parse_glyph_range("-3").is_err());
+        assert!(parse_glyph_range("glyph").is_err());
+    }
+
+    #[test]
+    fn parse_valid_timed_mask() {
+        assert_eq!(
+            parse_timed_mask("after-disarm=all"),
+            Ok(TimedMask {
+                target: MaskTarget::All,
+                trigger: MaskTrigger::AfterDisarm,
+            })
+        );
+        assert_eq!(
+            parse_timed_mask("until-armed=glyph:0x80-0x8f"),
+            Ok(TimedMask {
+                target: MaskTarget::Glyphs(0x80..=0x8f),
+                trigger: MaskTrigger::UntilArmed,
+            })
+        );
+        assert_eq!(
+            parse_timed_mask("0:10..1:00=12,3"),
+            Ok(TimedMask {
+                target: MaskTarget::Positions(HashSet::from([Coordinates::new(12, 3)])),
+                trigger: MaskTrigger::Time {
+                    start_secs: 10.0,
+                    end_secs: 60.0,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn reject_invalid_timed_mask() {
+        assert!(parse_timed_mask("all").is_err());
+        assert!(parse_timed_mask("armed=all").is_err());
+        assert!(parse_timed_mask("20..10=all").is_err());
+        assert!(parse_timed_mask("until-armed=12").is_err());
+    }
+
+    #[test]
+    fn parse_valid_chroma_key() {
+        assert_eq!(
+            parse_chroma_key("#01B140"),
+            Ok([1.0 / 255.0, 177.0 / 255.0, 64.0 / 255.0])
+        );
+        assert_eq!(parse_chroma_key("ff00ff"), Ok([1.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn reject_invalid_chroma_key() {
+        assert!(parse_chroma_key("#01B14").is_err());
+        assert!(parse_chroma_key("GGGGGG").is_err());
+    }
+
+    #[test]
+    fn parse_valid_frame_size() {
+        assert_eq!(parse_frame_size("1920x1080"), Ok((1920, 1080)));
+        assert_eq!(parse_frame_size("1280X720"), Ok((1280, 720)));
+    }
+
+    #[test]
+    fn reject_invalid_frame_size() {
+        assert!(parse_frame_size("1920").is_err());
+        assert!(parse_frame_size("0x1080").is_err());
+        assert!(parse_frame_size("widexhigh").is_err());
+    }
+
+    #[test]
+    fn parse_valid_timestamp() {
+        assert_eq!(parse_timestamp("42"), Ok(Duration::from_secs(42)));
+        assert_eq!(parse_timestamp("1.5"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_timestamp("02:03"), Ok(Duration::from_secs(123)));
+        assert_eq!(parse_timestamp("1:00:10.250"), Ok(Duration::from_millis(3_610_250)));
+    }
+
+    #[test]
+    fn reject_invalid_timestamp() {
+        assert!(parse_timestamp("").is_err());
+        assert!(parse_timestamp("-3").is_err());
+        assert!(parse_timestamp("a:10").is_err());
+        assert!(parse_timestamp("1:2:3:4").is_err());
+    }
+
+    #[test]
+    fn cli_arguments_are_valid() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+}
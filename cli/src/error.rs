@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use backend::{
+    ffmpeg::{FfmpegError, VideoInfoError},
+    font::FontFileError,
+    osd::OsdFileError,
+    srt::SrtFileError,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("ffmpeg and/or ffprobe could not be found")]
+    MissingDependencies,
+
+    #[error("Failed to read video file: {source}")]
+    VideoInfo {
+        #[from]
+        source: VideoInfoError,
+    },
+
+    #[error("{source}")]
+    OsdFile {
+        #[from]
+        source: OsdFileError,
+    },
+
+    #[error("No OSD or SRT data found for the video")]
+    NoOsdData,
+
+    #[error("{source}")]
+    SrtFile {
+        #[from]
+        source: SrtFileError,
+    },
+
+    #[error("{source}")]
+    FontFile {
+        #[from]
+        source: FontFileError,
+    },
+
+    #[error("No bundled font available for FC firmware {0}")]
+    NoBundledFont(String),
+
+    #[error("Unknown encoder {name}, available encoders: {available}")]
+    UnknownEncoder { name: String, available: String },
+
+    #[error("Failed to start video render: {source}")]
+    StartRender {
+        #[from]
+        source: FfmpegError,
+    },
+
+    #[error("Invalid render range: start ({start:?}) must be before end ({end:?})")]
+    InvalidTimeRange { start: Duration, end: Duration },
+
+    #[error("Nothing to render, the video or render range is shorter than one frame")]
+    NoFramesToRender,
+
+    #[error("Render failed: {0}")]
+    RenderFailed(String),
+}
@@ -0,0 +1,319 @@
+use std::{io::Write, path::PathBuf, process::ExitCode};
+
+use backend::{
+    ffmpeg::{
+        ffmpeg_available, ffprobe_available, start_video_render, Encoder, FromFfmpegMessage, RenderSettings, TimeRange,
+        ToFfmpegMessage, VideoInfo,
+    },
+    font::{bundled_fonts::get_bundled_font, FontFile},
+    osd::{
+        artlynk::{self, ExtractionHandle},
+        GridSize, OsdFile, OsdFileError, OsdOptions, OsdSourceRegistry, SourceContext,
+    },
+    overlay::{get_character_size, scaled_character_size},
+    srt::{SrtFile, SrtOptions},
+    util::get_output_video_path,
+};
+use clap::Parser;
+use tracing_subscriber::filter::LevelFilter;
+
+use crate::{args::Cli, error::CliError};
+
+mod args;
+mod error;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(if cli.verbose {
+            LevelFilter::INFO
+        } else {
+            LevelFilter::WARN
+        })
+        .init();
+
+    match render(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn render(cli: &Cli) -> Result<(), CliError> {
+    if !ffmpeg_available(&cli.ffmpeg_path) || !ffprobe_available(&cli.ffprobe_path) {
+        return Err(CliError::MissingDependencies);
+    }
+
+    let osd_file = open_osd_file(cli)?;
+    let srt_file = open_srt_file(cli)?;
+    if osd_file.is_none() && srt_file.is_none() {
+        return Err(CliError::NoOsdData);
+    }
+
+    let video_info = match (&cli.video, &osd_file) {
+        (Some(video), _) => VideoInfo::get(video, &cli.ffprobe_path)?,
+        (None, Some(osd_file)) => {
+            let (width, height) = cli.render_args.size;
+            VideoInfo::synthetic(width, height, cli.render_args.fps, osd_file.duration)
+        }
+        // Clap requires --osd when there is no video
+        (None, None) => unreachable!(),
+    };
+
+    // The font is only needed to render the OSD, SRT data is drawn with a bundled TrueType font
+    let font_file = match (&cli.font, &osd_file) {
+        (Some(font_path), Some(_)) => Some(FontFile::open(font_path.clone())?),
+        (None, Some(osd_file)) => {
+            let character_size = get_character_size(video_info.width, video_info.height);
+            let font_file = get_bundled_font(&osd_file.fc_firmware, &character_size)
+                .ok_or_else(|| CliError::NoBundledFont(osd_file.fc_firmware.to_string()))?;
+            Some(font_file)
+        }
+        (_, None) => None,
+    };
+
+    let mut osd_options = cli.osd_args.osd_options();
+    if let (None, Some(osd_file)) = (cli.osd_args.osd_x, &osd_file) {
+        osd_options.position.x =
+            centered_horizontal_position(&video_info, osd_file, &osd_options, cli.render_args.pad_4_3_to_16_9);
+    }
+    osd_options.osd_playback_speed_factor = match &osd_file {
+        Some(osd_file) if osd_options.adjust_playback_speed => {
+            video_info.duration.as_secs_f32() / osd_file.duration.as_secs_f32()
+        }
+        _ => 1.0,
+    };
+
+    let srt_options = cli.srt_args.srt_options(default_srt_options(srt_file.as_ref()));
+
+    let render_settings = RenderSettings {
+        encoder: find_encoder(&cli.ffmpeg_path, &cli.render_args.encoder)?,
+        bitrate_mbps: cli
+            .render_args
+            .bitrate
+            .or_else(|| {
+                cli.video
+                    .as_ref()
+                    .map(|_| (video_info.bitrate as f32 / 1_000_000.0).round() as u32)
+            })
+            .unwrap_or(RenderSettings::default().bitrate_mbps),
+        upscale: cli.render_args.upscale.into(),
+        pad_4_3_to_16_9: cli.render_args.pad_4_3_to_16_9,
+        use_chroma_key: cli.render_args.chroma_key.is_some(),
+        chroma_key: cli
+            .render_args
+            .chroma_key
+            .unwrap_or(RenderSettings::default().chroma_key),
+        audio: cli.render_args.audio.into(),
+        output_format: cli.render_args.format.into(),
+        time_range: time_range(cli, &video_info)?,
+        ..Default::default()
+    };
+    let total_frames = render_settings.frames_to_render(&video_info);
+    if total_frames == 0 {
+        return Err(CliError::NoFramesToRender);
+    }
+
+    // Without a video the OSD file is given explicitly so it can be used to name the output
+    let input_path = cli.video.as_ref().or(cli.osd.as_ref()).unwrap();
+    let output_video = cli
+        .output
+        .clone()
+        .unwrap_or_else(|| get_output_video_path(input_path, render_settings.output_format));
+    println!("Rendering {} to {}", input_path.display(), output_video.display());
+    if let Some(osd_file) = osd_file.as_ref().filter(|o| !o.discarded.is_empty()) {
+        println!(
+            "Skipped {} of damaged OSD file {}",
+            osd_file.discarded,
+            osd_file.file_path.display()
+        );
+    }
+
+    let srt_font = rusttype::Font::try_from_bytes(include_bytes!("../../resources/fonts/AzeretMono-Regular.ttf"))
+        .expect("Failed to load bundled SRT font");
+
+    let osd_grid_size = osd_file.as_ref().map_or(GridSize::HD, |o| o.grid_size);
+    let (to_ffmpeg_sender, from_ffmpeg_receiver) = start_video_render(
+        &cli.ffmpeg_path,
+        cli.video.as_ref(),
+        &output_video,
+        osd_file.map(|o| o.frames).unwrap_or_default(),
+        osd_grid_size,
+        srt_file.map(|s| s.frames).unwrap_or_default(),
+        font_file,
+        srt_font,
+        &osd_options,
+        &srt_options,
+        &video_info,
+        &render_settings,
+    )?;
+
+    let mut progress_pct = 0.0;
+    let mut decoder_finished = false;
+    for message in from_ffmpeg_receiver.iter() {
+        match message {
+            FromFfmpegMessage::Progress(p) => {
+                progress_pct = p.frame as f32 / total_frames as f32 * 100.0;
+                eprint!(
+                    "\rFrame {}/{} ({:.1}%), fps: {:.1}, speed: {:.3}x  ",
+                    p.frame, total_frames, progress_pct, p.fps, p.speed
+                );
+                std::io::stderr().flush().ok();
+            }
+            FromFfmpegMessage::DecoderFatalError(e) | FromFfmpegMessage::EncoderFatalError(e) => {
+                eprintln!();
+                to_ffmpeg_sender.send(ToFfmpegMessage::AbortRender).ok();
+                return Err(CliError::RenderFailed(e));
+            }
+            FromFfmpegMessage::DecoderFinished => decoder_finished = true,
+            FromFfmpegMessage::EncoderFinished => {
+                eprintln!();
+                to_ffmpeg_sender.send(ToFfmpegMessage::AbortRender).ok();
+                // Same check as the GUI: the encoder sometimes reaches EOF early so only treat it as an error
+                // when nothing was rendered at all.
+                if !decoder_finished && progress_pct < 0.1 {
+                    return Err(CliError::RenderFailed("Encoder unexpectedly finished".into()));
+                }
+                break;
+            }
+        }
+    }
+
+    println!("Done");
+    Ok(())
+}
+
+/// The OSD file given on the command line, or the OSD data found for the video, if any.
+fn open_osd_file(cli: &Cli) -> Result<Option<OsdFile>, CliError> {
+    let registry = OsdSourceRegistry::default();
+    let context = SourceContext {
+        ffmpeg_path: cli.ffmpeg_path.clone(),
+        // Discarded data is reported when the render starts
+        lenient: !cli.strict_osd,
+        cache_dir: artlynk::default_cache_dir(),
+        handle: ExtractionHandle::default(),
+    };
+
+    if let Some(osd_path) = &cli.osd {
+        return Ok(Some(registry.open(osd_path, &context)?));
+    }
+
+    let Some(video) = &cli.video else {
+        return Ok(None);
+    };
+    let osd_path = video.with_extension("osd");
+    if osd_path.exists() {
+        return Ok(Some(registry.open(&osd_path, &context)?));
+    }
+
+    tracing::info!("No OSD file found, looking for OSD data in the video");
+    match registry.open(video, &context) {
+        Ok(osd_file) => Ok(Some(osd_file)),
+        Err(OsdFileError::NoOsdData { .. } | OsdFileError::UnsupportedFormat { .. }) => {
+            tracing::warn!("No OSD data found for the video, only rendering SRT data");
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn open_srt_file(cli: &Cli) -> Result<Option<SrtFile>, CliError> {
+    let srt_path = match &cli.srt {
+        Some(srt_path) => srt_path.clone(),
+        None => match &cli.video {
+            Some(video) if video.with_extension("srt").exists() => video.with_extension("srt"),
+            _ => return Ok(None),
+        },
+    };
+    Ok(Some(SrtFile::open(srt_path)?))
+}
+
+/// Same defaults as the GUI applies when an SRT file is imported.
+fn default_srt_options(srt_file: Option<&SrtFile>) -> SrtOptions {
+    let Some(srt_file) = srt_file else {
+        return SrtOptions::default();
+    };
+
+    let file_name = srt_file
+        .file_path
+        .file_name()
+        .map(|f| f.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mut srt_options = if file_name.starts_with("avatar") || file_name.starts_with("ascent") {
+        SrtOptions::walksnail_optimized()
+    } else {
+        SrtOptions::default()
+    };
+    srt_options.show_distance &= srt_file.has_distance;
+    srt_options
+}
+
+fn time_range(cli: &Cli, video_info: &VideoInfo) -> Result<Option<TimeRange>, CliError> {
+    if cli.render_args.start.is_none() && cli.render_args.end.is_none() {
+        return Ok(None);
+    }
+
+    let start = cli.render_args.start.unwrap_or_default();
+    let end = cli
+        .render_args
+        .end
+        .unwrap_or(video_info.duration)
+        .min(video_info.duration);
+    if start >= end {
+        return Err(CliError::InvalidTimeRange { start, end });
+    }
+    Ok(Some(TimeRange { start, end }))
+}
+
+fn find_encoder(ffmpeg_path: &PathBuf, name: &str) -> Result<Encoder, CliError> {
+    let encoders = Encoder::get_available_encoders(ffmpeg_path);
+    let encoder = encoders
+        .iter()
+        .find(|e| e.name == name)
+        .ok_or_else(|| CliError::UnknownEncoder {
+            name: name.to_string(),
+            available: encoders
+                .iter()
+                .filter(|e| e.detected)
+                .map(|e| e.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        })?;
+    if !encoder.detected {
+        tracing::warn!("Encoder {} was not detected, rendering will likely fail", encoder.name);
+    }
+    Ok(encoder.clone())
+}
+
+/// Horizontal OSD position that centers the first non-empty OSD frame, like the "Center" button in the GUI.
+fn centered_horizontal_position(
+    video_info: &VideoInfo,
+    osd_file: &OsdFile,
+    osd_options: &OsdOptions,
+    pad_4_3_to_16_9: bool,
+) -> i32 {
+    let Some(frame) = osd_file.frames.iter().find(|f| !f.glyphs.is_empty()) else {
+        return 0;
+    };
+
+    let is_4_3 = (video_info.width as f32 / video_info.height as f32) < 1.5;
+    let effective_width = if pad_4_3_to_16_9 && is_4_3 {
+        video_info.height * 16 / 9
+    } else {
+        video_info.width
+    };
+    let base_char_size = get_character_size(effective_width, video_info.height);
+    let (scaled_char_width, _) = scaled_character_size(&base_char_size, &osd_file.grid_size, osd_options);
+    let scaled_char_width = scaled_char_width as i32;
+
+    let min_x = frame.glyphs.iter().map(|g| g.grid_position.x).min().unwrap() as i32;
+    let max_x = frame.glyphs.iter().map(|g| g.grid_position.x).max().unwrap() as i32;
+    let pixel_range = (max_x - min_x + 1) * scaled_char_width;
+    // The renderer already moves the OSD right by the width of the left bar when padding, so take that off again
+    let left_bar_width = (effective_width - video_info.width) as i32 / 2;
+    (effective_width as i32 - pixel_range) / 2 - left_bar_width - min_x * scaled_char_width
+}
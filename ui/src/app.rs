@@ -7,7 +7,7 @@ use backend::{
     config::AppConfig,
     ffmpeg::{Encoder, FromFfmpegMessage, RenderSettings, ToFfmpegMessage, VideoInfo},
     font::{self, FontFile},
-    osd::{OsdFile, OsdFileError, OsdOptions},
+    osd::{artlynk::ExtractionHandle, FlightState, GridSize, MaskTrigger, OsdFile, OsdFileError, OsdOptions},
     srt::{SrtFile, SrtOptions},
 };
 use crossbeam_channel::{Receiver, Sender};
@@ -20,6 +20,7 @@ use poll_promise::Promise;
 
 use crate::{
     osd_preview::create_osd_preview,
+    render_queue::RenderQueue,
     render_status::RenderStatus,
     util::{set_custom_fonts, set_style},
 };
@@ -36,6 +37,7 @@ pub struct WalksnailOsdTool {
     pub to_ffmpeg_sender: Option<Sender<ToFfmpegMessage>>,
     pub from_ffmpeg_receiver: Option<Receiver<FromFfmpegMessage>>,
     pub render_status: RenderStatus,
+    pub render_queue: RenderQueue,
     pub encoders: Vec<Encoder>,
     pub dependencies: Dependencies,
     pub render_settings: RenderSettings,
@@ -46,7 +48,8 @@ pub struct WalksnailOsdTool {
     pub about_window_open: bool,
     pub dark_mode: bool,
     pub app_update: AppUpdate,
-    pub artlynk_extraction_promise: Option<Promise<Result<Option<OsdFile>, OsdFileError>>>,
+    pub artlynk_extraction_promise: Option<Promise<Result<OsdFile, OsdFileError>>>,
+    pub artlynk_extraction_handle: Option<ExtractionHandle>,
     pub app_version: String,
     pub target: String,
 }
@@ -137,6 +140,14 @@ pub struct OsdPreview {
     #[derivative(Default(value = "1"))]
     pub preview_frame: u32,
     pub mask_edit_mode_enabled: bool,
+    pub glyph_mask_window_open: bool,
+    /// Textures of the font characters for the glyph mask window, and the font they were made from.
+    pub glyph_textures: Vec<TextureHandle>,
+    pub glyph_textures_font: Option<PathBuf>,
+    pub glyph_range_start: u16,
+    pub glyph_range_end: u16,
+    pub redaction_text: String,
+    pub timed_mask_trigger: MaskTrigger,
 }
 
 pub struct UiDimensions {
@@ -189,11 +200,14 @@ impl eframe::App for WalksnailOsdTool {
         self.update_window(ctx);
 
         // Keep updating the UI thread when rendering to make sure the indicated progress is up-to-date
-        if self.render_status.is_in_progress() {
+        if self.render_status.is_in_progress() || self.render_queue.is_running() {
             ctx.request_repaint();
         }
 
         self.receive_ffmpeg_message();
+        if let Some(srt_font) = &self.srt_font {
+            self.render_queue.update(&self.dependencies.ffmpeg_path, srt_font);
+        }
         self.poll_update_check();
         self.poll_artlynk_extraction(ctx);
 
@@ -238,13 +252,24 @@ impl WalksnailOsdTool {
     }
 
     pub fn update_osd_preview(&mut self, ctx: &egui::Context) {
-        if let (Some(video_info), Some(osd_file), Some(font_file)) = (&self.video_info, &self.osd_file, &self.font_file)
-        {
-            let osd_frame = osd_file
+        let osd_frame = self.osd_file.as_ref().map(|osd_file| {
+            osd_file
                 .frames
                 .get(self.osd_preview.preview_frame as usize - 1)
-                .unwrap();
-            let timestamp = osd_frame.time_millis as f32 / 1000.0;
+                .unwrap()
+        });
+        let osd_frame_and_font = osd_frame.zip(self.font_file.as_ref());
+        if let (Some(video_info), true) = (&self.video_info, osd_frame_and_font.is_some() || self.srt_loaded()) {
+            // Without an OSD file the preview frame slider steps through the SRT frames instead
+            let timestamp = match osd_frame {
+                Some(osd_frame) => osd_frame.time_millis as f32 / 1000.0,
+                None => self
+                    .srt_file
+                    .as_ref()
+                    .and_then(|srt_file| srt_file.frames.get(self.osd_preview.preview_frame as usize - 1))
+                    .map(|srt_frame| srt_frame.start_time_secs)
+                    .unwrap_or_default(),
+            };
 
             let srt_frame = self.srt_file.as_ref().map(|srt_file| {
                 srt_file
@@ -260,14 +285,28 @@ impl WalksnailOsdTool {
                     })
             });
 
+            // Timed masks depend on the arming messages of all frames up to the previewed one
+            let preview_time_secs = self.preview_video_time().unwrap_or_default().as_secs_f32();
+            let flight_state = match &self.osd_file {
+                Some(osd_file) => FlightState::after_frames(
+                    osd_file.frames.iter().take(self.osd_preview.preview_frame as usize),
+                    preview_time_secs,
+                ),
+                None => FlightState {
+                    time_secs: preview_time_secs,
+                    ..Default::default()
+                },
+            };
+
             let osd_preview_image = create_osd_preview(
                 video_info.width,
                 video_info.height,
-                osd_frame,
+                osd_frame_and_font,
+                &self.osd_file.as_ref().map_or(GridSize::HD, |o| o.grid_size),
                 srt_frame,
-                font_file,
                 self.srt_font.as_ref().unwrap(),
                 &self.osd_options,
+                &flight_state,
                 &self.srt_options,
                 self.render_settings.pad_4_3_to_16_9,
             );
@@ -287,13 +326,14 @@ impl WalksnailOsdTool {
         if let (Some(tx), Some(rx), Some(video_info)) =
             (&self.to_ffmpeg_sender, &self.from_ffmpeg_receiver, &self.video_info)
         {
+            let total_frames = self.render_settings.frames_to_render(video_info);
             while let Ok(message) = rx.try_recv() {
                 if matches!(message, FromFfmpegMessage::EncoderFatalError(_))
                     || matches!(message, FromFfmpegMessage::EncoderFinished)
                 {
                     tx.send(ToFfmpegMessage::AbortRender).ok();
                 }
-                self.render_status.update_from_ffmpeg_message(message, video_info)
+                self.render_status.update_from_ffmpeg_message(message, total_frames)
             }
         }
     }
@@ -311,10 +351,10 @@ impl WalksnailOsdTool {
 
     fn poll_artlynk_extraction(&mut self, ctx: &egui::Context) {
         if let Some(promise) = &self.artlynk_extraction_promise {
-            let ready_result: Option<&Result<Option<OsdFile>, OsdFileError>> = promise.ready();
+            let ready_result: Option<&Result<OsdFile, OsdFileError>> = promise.ready();
             if let Some(result) = ready_result {
                 match result {
-                    Ok(Some(osd_file)) => {
+                    Ok(osd_file) => {
                         tracing::info!(
                             "Artlynk OSD extraction finished. Found {} frames.",
                             osd_file.frame_count
@@ -329,7 +369,7 @@ impl WalksnailOsdTool {
                         self.update_osd_preview(ctx);
                         self.auto_resize_window(ctx);
                     }
-                    Ok(None) => {
+                    Err(OsdFileError::NoOsdData { .. }) => {
                         tracing::info!("Artlynk OSD extraction finished. No OSD data found.");
                     }
                     Err(e) => {
@@ -337,6 +377,7 @@ impl WalksnailOsdTool {
                     }
                 }
                 self.artlynk_extraction_promise = None;
+                self.artlynk_extraction_handle = None;
             }
         }
     }
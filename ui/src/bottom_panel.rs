@@ -1,8 +1,12 @@
-use backend::ffmpeg::{start_video_render, ToFfmpegMessage};
+use backend::{
+    ffmpeg::{start_video_render, ToFfmpegMessage},
+    osd::GridSize,
+    util::get_output_video_path,
+};
 use egui::{vec2, Align, Button, Color32, Layout, ProgressBar, RichText, Ui};
 
 use super::{util::format_minutes_seconds, WalksnailOsdTool};
-use crate::{render_status::Status, util::get_output_video_path};
+use crate::render_status::Status;
 
 impl WalksnailOsdTool {
     pub fn render_bottom_panel(&mut self, ctx: &egui::Context) {
@@ -21,31 +25,33 @@ impl WalksnailOsdTool {
         if self.render_status.is_not_in_progress() {
             if ui
                 .add_enabled(
-                    self.all_files_loaded(),
+                    self.ready_to_render() && !self.render_queue.is_running(),
                     Button::new("Start render").min_size(button_size),
                 )
-                .on_disabled_hover_text("First load video, OSD, SRT and font files")
+                .on_disabled_hover_text(
+                    "First load a video with OSD and font files or an SRT file and wait for the render queue to finish",
+                )
                 .clicked()
             {
                 tracing::info!("Start render button clicked");
                 self.render_status.start_render();
-                if let (Some(video_path), Some(osd_file), Some(font_file), Some(video_info)) =
-                    (&self.video_file, &self.osd_file, &self.font_file, &self.video_info)
-                {
-                    self.osd_options.osd_playback_speed_factor = if self.osd_options.adjust_playback_speed {
-                        let video_duration = video_info.duration;
-                        let osd_duration = osd_file.duration;
-                        video_duration.as_secs_f32() / osd_duration.as_secs_f32()
-                    } else {
-                        1.0
+                if let (Some(video_path), Some(video_info)) = (&self.video_file, &self.video_info) {
+                    self.osd_options.osd_playback_speed_factor = match &self.osd_file {
+                        Some(osd_file) if self.osd_options.adjust_playback_speed => {
+                            let video_duration = video_info.duration;
+                            let osd_duration = osd_file.duration;
+                            video_duration.as_secs_f32() / osd_duration.as_secs_f32()
+                        }
+                        _ => 1.0,
                     };
                     match start_video_render(
                         &self.dependencies.ffmpeg_path,
-                        video_path,
-                        &get_output_video_path(video_path),
-                        osd_file.frames.clone(),
+                        Some(video_path),
+                        &get_output_video_path(video_path, self.render_settings.output_format),
+                        self.osd_file.as_ref().map(|o| o.frames.clone()).unwrap_or_default(),
+                        self.osd_file.as_ref().map_or(GridSize::HD, |o| o.grid_size),
                         self.srt_file.as_ref().map(|s| s.frames.clone()).unwrap_or_default(),
-                        font_file.clone(),
+                        self.font_file.clone(),
                         self.srt_font.as_ref().unwrap().clone(),
                         &self.osd_options,
                         &self.srt_options,
@@ -65,6 +71,18 @@ impl WalksnailOsdTool {
                     };
                 }
             }
+
+            if ui
+                .add_enabled(
+                    self.ready_to_render(),
+                    Button::new("Add to queue").min_size(button_size),
+                )
+                .on_hover_text("Render the loaded files with the current settings after the jobs already in the queue")
+                .on_disabled_hover_text("First load a video with OSD and font files or an SRT file")
+                .clicked()
+            {
+                self.add_to_render_queue();
+            }
         } else {
             if ui.add(Button::new("Stop render").min_size(button_size)).clicked() {
                 tracing::info!("Stop render button clicked");
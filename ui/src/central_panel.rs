@@ -1,13 +1,20 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use backend::{overlay::get_character_size, util::Coordinates};
+use backend::{
+    ffmpeg::TimeRange,
+    osd::{GridSize, MaskTarget, MaskTrigger, RedactionAction, RedactionPattern, RedactionRule, TimedMask},
+    overlay::{get_character_size, scaled_character_size},
+    util::Coordinates,
+};
 use egui::{
-    vec2, Button, CentralPanel, Checkbox, CollapsingHeader, Color32, CursorIcon, Grid, Image, Rect, RichText,
-    ScrollArea, Sense, Slider, Stroke, Ui, Vec2,
+    vec2, Button, CentralPanel, Checkbox, CollapsingHeader, Color32, CursorIcon, DragValue, Grid, Image, ImageButton,
+    ProgressBar, Rect, RichText, ScrollArea, Sense, Slider, Stroke, TextEdit, Ui, Vec2, Window,
 };
 
 use crate::{
-    util::{separator_with_space, tooltip_text},
+    osd_preview::{calculate_horizontal_offset, calculate_vertical_offset},
+    render_status::Status,
+    util::{format_minutes_seconds, separator_with_space, tooltip_text},
     WalksnailOsdTool,
 };
 
@@ -30,8 +37,14 @@ impl WalksnailOsdTool {
                 separator_with_space(ui, 10.0);
 
                 self.rendering_options(ui, ctx);
+
+                separator_with_space(ui, 10.0);
+
+                self.render_queue(ui);
             });
         });
+
+        self.glyph_mask_window(ctx);
     }
 
     fn osd_options(&mut self, ui: &mut Ui, ctx: &egui::Context) {
@@ -40,10 +53,22 @@ impl WalksnailOsdTool {
         CollapsingHeader::new(RichText::new("OSD Options").heading())
             .default_open(true)
             .show_unindented(ui, |ui| {
-                if self.artlynk_extraction_promise.is_some() {
+                if let Some(handle) = &self.artlynk_extraction_handle {
                     ui.horizontal(|ui| {
                         ui.spinner();
                         ui.label(RichText::new("Scanning for OSD data...").color(Color32::DEBUG_COLOR));
+                        ui.add(
+                            ProgressBar::new(handle.progress())
+                                .desired_width(150.0)
+                                .show_percentage(),
+                        );
+                        if ui
+                            .add_enabled(!handle.is_cancelled(), Button::new("Stop"))
+                            .on_hover_text(tooltip_text("Stop scanning and use the OSD data found so far."))
+                            .clicked()
+                        {
+                            handle.cancel();
+                        }
                     });
                     ui.add_space(5.0);
                 }
@@ -89,17 +114,13 @@ impl WalksnailOsdTool {
                                         video_info.width
                                     };
                                     let base_char_size = get_character_size(effective_width, video_info.height);
-                                    let scale_factor = self.osd_options.scale / 100.0;
-                                    let scaled_char_height = (base_char_size.height() as f32 * scale_factor).round() as i32;
+                                    let (_, scaled_char_height) = scaled_character_size(&base_char_size, &osd_file.grid_size, &self.osd_options);
 
                                     let frame = osd_file
                                         .frames
                                         .get(self.osd_preview.preview_frame as usize - 1)
                                         .unwrap();
-                                    let min_y = frame.glyphs.iter().map(|g| g.grid_position.y).min().unwrap() as i32;
-                                    let max_y = frame.glyphs.iter().map(|g| g.grid_position.y).max().unwrap() as i32;
-                                    let pixel_range = (max_y - min_y + 1) * scaled_char_height;
-                                    self.osd_options.position.y = (video_info.height as i32 - pixel_range) / 2 - min_y * scaled_char_height;
+                                    self.osd_options.position.y = calculate_vertical_offset(video_info.height, frame, scaled_char_height);
                                     changed |= true
                                 }
                             }
@@ -129,6 +150,141 @@ impl WalksnailOsdTool {
                         });
                         ui.end_row();
 
+                        ui.label("Mask symbols")
+                            .on_hover_text(tooltip_text("Select font characters that should not be rendered wherever they are on the OSD. This hides elements that move around, like the GPS coordinates or the home arrow."));
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(self.font_loaded(), Button::new("Select"))
+                                .on_disabled_hover_text(tooltip_text("First load a font file")).clicked() {
+                                self.osd_preview.glyph_mask_window_open = !self.osd_preview.glyph_mask_window_open;
+                            }
+                            if ui.button("Reset").clicked() {
+                                self.osd_options.reset_glyph_mask();
+                                changed |= true;
+                            }
+                            let masked_glyphs = self.osd_options.masked_glyphs.len();
+                            let masked_ranges = self.osd_options.masked_glyph_ranges.len();
+                            ui.label(format!("{masked_glyphs} symbols and {masked_ranges} ranges masked"));
+                        });
+                        ui.end_row();
+
+                        ui.label("Timed masks")
+                            .on_hover_text(tooltip_text("Masks that only apply part of the time, e.g. to hide the warnings before arming or the statistics after landing. Positions selected with the grid mask or the symbol range of \"Mask symbols\" can be moved into a timed mask."));
+                        ui.vertical(|ui| {
+                            let mut deleted = None;
+                            for (i, mask) in self.osd_options.timed_masks.iter().enumerate() {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("{} {}", mask.target, mask.trigger));
+                                    if ui.button("Delete").clicked() {
+                                        deleted = Some(i);
+                                    }
+                                });
+                            }
+                            if let Some(i) = deleted {
+                                self.osd_options.timed_masks.remove(i);
+                                changed |= true;
+                            }
+
+                            ui.horizontal(|ui| {
+                                let trigger = &mut self.osd_preview.timed_mask_trigger;
+                                let is_time = matches!(trigger, MaskTrigger::Time { .. });
+                                egui::ComboBox::from_id_source("timed_mask_trigger")
+                                    .selected_text(match trigger {
+                                        MaskTrigger::UntilArmed => "Until armed",
+                                        MaskTrigger::AfterDisarm => "After disarm",
+                                        MaskTrigger::Time { .. } => "Time range",
+                                    })
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(trigger, MaskTrigger::UntilArmed, "Until armed");
+                                        ui.selectable_value(trigger, MaskTrigger::AfterDisarm, "After disarm")
+                                            .on_hover_text(tooltip_text("Starts when the OSD shows DISARMED or the Betaflight statistics screen. Use a time range for firmware that shows neither."));
+                                        if ui.selectable_label(is_time, "Time range").clicked() && !is_time {
+                                            *trigger = MaskTrigger::Time { start_secs: 0.0, end_secs: 10.0 };
+                                        }
+                                    });
+                                if let MaskTrigger::Time { start_secs, end_secs } = trigger {
+                                    // Keep the start before the end, like the CLI requires
+                                    let (start, end) = (*start_secs, *end_secs);
+                                    ui.add(DragValue::new(start_secs).clamp_range(0.0..=(end - 0.1).max(0.0)).suffix(" s"));
+                                    ui.label("to");
+                                    ui.add(DragValue::new(end_secs).clamp_range(start + 0.1..=f32::MAX).suffix(" s"));
+                                }
+                            });
+
+                            ui.horizontal(|ui| {
+                                let trigger = self.osd_preview.timed_mask_trigger;
+                                let mut target = None;
+                                if ui.button("Add for whole OSD").clicked() {
+                                    target = Some(MaskTarget::All);
+                                }
+                                let has_positions = !self.osd_options.masked_grid_positions.is_empty();
+                                if ui.add_enabled(has_positions, Button::new("Add for masked positions")).clicked() {
+                                    target = Some(MaskTarget::Positions(std::mem::take(&mut self.osd_options.masked_grid_positions)));
+                                }
+                                let glyph_range = self.osd_preview.glyph_range_start..=self.osd_preview.glyph_range_end;
+                                if ui.add_enabled(!glyph_range.is_empty(), Button::new("Add for symbol range")).clicked() {
+                                    target = Some(MaskTarget::Glyphs(glyph_range));
+                                }
+                                if let Some(target) = target {
+                                    self.osd_options.timed_masks.push(TimedMask { target, trigger });
+                                    changed |= true;
+                                }
+                            });
+                        });
+                        ui.end_row();
+
+                        ui.label("Redact text")
+                            .on_hover_text(tooltip_text("Hide text wherever it shows up on the OSD, like the pilot name or the GPS coordinates. Matches can be removed or replaced with *."));
+                        ui.vertical(|ui| {
+                            let mut deleted = None;
+                            for (i, rule) in self.osd_options.redaction_rules.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    ui.label(rule.pattern.to_string());
+                                    let mut replace = matches!(rule.action, RedactionAction::Replace(_));
+                                    if ui.checkbox(&mut replace, "Replace with *").changed() {
+                                        rule.action = if replace {
+                                            RedactionAction::Replace('*')
+                                        } else {
+                                            RedactionAction::Remove
+                                        };
+                                        changed |= true;
+                                    }
+                                    if ui.button("Delete").clicked() {
+                                        deleted = Some(i);
+                                    }
+                                });
+                            }
+                            if let Some(i) = deleted {
+                                self.osd_options.redaction_rules.remove(i);
+                                changed |= true;
+                            }
+
+                            ui.horizontal(|ui| {
+                                ui.add(TextEdit::singleline(&mut self.osd_preview.redaction_text).desired_width(120.0));
+                                let text = self.osd_preview.redaction_text.trim().to_string();
+                                if ui.add_enabled(!text.is_empty(), Button::new("Add text")).clicked() {
+                                    self.osd_options.redaction_rules.push(RedactionRule {
+                                        pattern: RedactionPattern::Text(text),
+                                        action: RedactionAction::Remove,
+                                    });
+                                    self.osd_preview.redaction_text.clear();
+                                    changed |= true;
+                                }
+                                let has_coordinates = self
+                                    .osd_options
+                                    .redaction_rules
+                                    .iter()
+                                    .any(|rule| rule.pattern == RedactionPattern::Coordinates);
+                                if ui.add_enabled(!has_coordinates, Button::new("Add GPS coordinates")).clicked() {
+                                    self.osd_options.redaction_rules.push(RedactionRule {
+                                        pattern: RedactionPattern::Coordinates,
+                                        action: RedactionAction::Remove,
+                                    });
+                                    changed |= true;
+                                }
+                            });
+                        });
+                        ui.end_row();
+
                         ui.label("OSD size")
                             .on_hover_text(tooltip_text("Scale of the OSD characters as a percentage. 100% is the default size for the video resolution."));
                         ui.horizontal(|ui| {
@@ -310,10 +466,11 @@ impl WalksnailOsdTool {
                         ui.label("Preview frame").on_hover_text(tooltip_text(
                             "The selected frame is also used for centering the OSD under OSD Options.",
                         ));
+                        let preview_frame_count = self.preview_frame_count();
                         let preview_frame_slider = ui.add(
                             Slider::new(
                                 &mut self.osd_preview.preview_frame,
-                                1..=self.osd_file.as_ref().map(|f| f.frame_count).unwrap_or(1),
+                                1..=preview_frame_count,
                             )
                             .smart_aim(false),
                         );
@@ -321,10 +478,87 @@ impl WalksnailOsdTool {
                             self.update_osd_preview(ctx);
                         }
                     });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Render range").on_hover_text(tooltip_text(
+                            "Only render part of the video. Use the preview frame slider to find the start and end of the part you want to keep.",
+                        ));
+                        let preview_time = self.preview_video_time();
+                        if ui.add_enabled(preview_time.is_some(), Button::new("Set in")).clicked() {
+                            self.set_time_range_start(preview_time.unwrap());
+                        }
+                        if ui.add_enabled(preview_time.is_some(), Button::new("Set out")).clicked() {
+                            self.set_time_range_end(preview_time.unwrap());
+                        }
+                        if ui.button("Reset").clicked() {
+                            self.render_settings.time_range = None;
+                        }
+                        match &self.render_settings.time_range {
+                            Some(time_range) => ui.label(format!(
+                                "{} - {}",
+                                format_minutes_seconds(&time_range.start),
+                                format_minutes_seconds(&time_range.end)
+                            )),
+                            None => ui.label("Full video"),
+                        };
+                    });
                 }
             });
     }
 
+    /// Number of steps of the preview frame slider: the OSD frames, or the SRT frames when there is no OSD file.
+    fn preview_frame_count(&self) -> u32 {
+        match (&self.osd_file, &self.srt_file) {
+            (Some(osd_file), _) => osd_file.frame_count,
+            (None, Some(srt_file)) => srt_file.frames.len().max(1) as u32,
+            (None, None) => 1,
+        }
+    }
+
+    /// Time in the video that matches the frame selected in the preview.
+    pub fn preview_video_time(&self) -> Option<Duration> {
+        let video_info = self.video_info.as_ref()?;
+        let preview_index = self.osd_preview.preview_frame as usize - 1;
+        let secs = match (&self.osd_file, &self.srt_file) {
+            (Some(osd_file), _) => {
+                let osd_frame = osd_file.frames.get(preview_index)?;
+                let playback_speed_factor = if self.osd_options.adjust_playback_speed {
+                    video_info.duration.as_secs_f32() / osd_file.duration.as_secs_f32()
+                } else {
+                    1.0
+                };
+                osd_frame.time_millis as f32 / 1000.0 * playback_speed_factor
+            }
+            (None, Some(srt_file)) => srt_file.frames.get(preview_index)?.start_time_secs,
+            (None, None) => return None,
+        };
+        Some(Duration::from_secs_f32(secs.max(0.0)).min(video_info.duration))
+    }
+
+    fn set_time_range_start(&mut self, start: Duration) {
+        let Some(video_info) = &self.video_info else { return };
+        let end = self
+            .render_settings
+            .time_range
+            .map(|r| r.end)
+            .filter(|end| *end > start)
+            .unwrap_or(video_info.duration);
+        self.render_settings.time_range = Some(TimeRange { start, end });
+    }
+
+    fn set_time_range_end(&mut self, end: Duration) {
+        if end.is_zero() {
+            return;
+        }
+        let start = self
+            .render_settings
+            .time_range
+            .map(|r| r.start)
+            .filter(|start| *start < end)
+            .unwrap_or_default();
+        self.render_settings.time_range = Some(TimeRange { start, end });
+    }
+
     fn draw_grid(&mut self, ui: &mut Ui, ctx: &egui::Context, image_rect: Rect) {
         let video_width = self.video_info.as_ref().unwrap().width as f32;
         let video_height = self.video_info.as_ref().unwrap().height as f32;
@@ -333,10 +567,12 @@ impl WalksnailOsdTool {
         let preview_width = image_rect.width();
         let preview_height = image_rect.height();
 
+        // Same area as the characters of the HD grid cover, see `GridSize::character_scale`
+        let grid_size = self.osd_file.as_ref().map_or(GridSize::HD, |o| o.grid_size);
         let grid_width = preview_width * 0.99375;
         let grid_height = preview_height;
-        let cell_width = grid_width / 53.0;
-        let cell_height = grid_height / 20.0;
+        let cell_width = grid_width / grid_size.width as f32;
+        let cell_height = grid_height / grid_size.height as f32;
 
         let painter = ui.painter_at(image_rect);
 
@@ -347,8 +583,8 @@ impl WalksnailOsdTool {
             .allocate_rect(image_rect, Sense::click())
             .on_hover_cursor(CursorIcon::Crosshair);
 
-        for i in 0..53 {
-            for j in 0..20 {
+        for i in 0..grid_size.width {
+            for j in 0..grid_size.height {
                 let rect = Rect::from_min_size(
                     top_left
                         + vec2(i as f32 * cell_width, j as f32 * cell_height)
@@ -382,13 +618,13 @@ impl WalksnailOsdTool {
 
         let line_stroke = Stroke::new(1.0, Color32::GRAY.gamma_multiply(0.5));
 
-        for i in 0..=53 {
+        for i in 0..=grid_size.width {
             let x = top_left.x + i as f32 * cell_width + horizontal_offset;
             let y_min = image_rect.y_range().min + vertical_offset;
             let y_max = image_rect.y_range().max + vertical_offset;
             painter.vline(x, y_min..=y_max, line_stroke);
         }
-        for i in 0..=20 {
+        for i in 0..=grid_size.height {
             let x_min = image_rect.x_range().min + horizontal_offset;
             let x_max = image_rect.x_range().max + horizontal_offset;
             let y = top_left.y + i as f32 * cell_height + vertical_offset;
@@ -396,6 +632,104 @@ impl WalksnailOsdTool {
         }
     }
 
+    /// Window with all characters of the loaded font to pick the ones to mask.
+    fn glyph_mask_window(&mut self, ctx: &egui::Context) {
+        if !self.osd_preview.glyph_mask_window_open {
+            return;
+        }
+        let Some(font_file) = &self.font_file else {
+            return;
+        };
+
+        if self.osd_preview.glyph_textures_font.as_ref() != Some(&font_file.file_path) {
+            self.osd_preview.glyph_textures = font_file
+                .characters()
+                .iter()
+                .enumerate()
+                .map(|(index, character)| {
+                    let image = egui::ColorImage::from_rgba_unmultiplied(
+                        [character.width() as usize, character.height() as usize],
+                        character,
+                    );
+                    ctx.load_texture(format!("Glyph {index}"), image, egui::TextureOptions::default())
+                })
+                .collect();
+            self.osd_preview.glyph_textures_font = Some(font_file.file_path.clone());
+        }
+
+        let glyph_count = self.osd_preview.glyph_textures.len();
+        let glyph_height = 30.0;
+        let glyph_size = vec2(
+            font_file.character_size.width() as f32 / font_file.character_size.height() as f32 * glyph_height,
+            glyph_height,
+        );
+        let mut changed = false;
+
+        Window::new("Mask symbols")
+            .open(&mut self.osd_preview.glyph_mask_window_open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Click a symbol to hide it on the OSD, click it again to show it.");
+                ui.add_space(5.0);
+
+                ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    Grid::new("glyph_mask").spacing(vec2(2.0, 2.0)).show(ui, |ui| {
+                        for (index, texture) in self.osd_preview.glyph_textures.iter().enumerate() {
+                            let index = index as u16;
+                            let masked = self.osd_options.get_glyph_mask(index);
+                            let mut image = Image::new((texture.id(), glyph_size));
+                            if masked {
+                                image = image.tint(Color32::RED);
+                            }
+                            if ui
+                                .add(ImageButton::new(image).selected(masked))
+                                .on_hover_text(format!("Symbol {index} (0x{index:02X})"))
+                                .clicked()
+                            {
+                                self.osd_options.toggle_glyph_mask(index);
+                                changed = true;
+                            }
+                            if (index + 1) % 16 == 0 {
+                                ui.end_row();
+                            }
+                        }
+                    });
+                });
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    let max_index = glyph_count.saturating_sub(1).min(u16::MAX as usize) as u16;
+                    ui.label("Range");
+                    ui.add(
+                        DragValue::new(&mut self.osd_preview.glyph_range_start)
+                            .hexadecimal(2, false, true)
+                            .clamp_range(0..=max_index),
+                    );
+                    ui.label("to");
+                    ui.add(
+                        DragValue::new(&mut self.osd_preview.glyph_range_end)
+                            .hexadecimal(2, false, true)
+                            .clamp_range(0..=max_index),
+                    );
+                    if ui.button("Mask range").clicked() {
+                        self.osd_options
+                            .mask_glyph_range(self.osd_preview.glyph_range_start..=self.osd_preview.glyph_range_end);
+                        changed = true;
+                    }
+                    if ui.button("Reset").clicked() {
+                        self.osd_options.reset_glyph_mask();
+                        changed = true;
+                    }
+                });
+            });
+
+        if changed {
+            self.update_osd_preview(ctx);
+            self.config_changed = Some(Instant::now());
+        }
+    }
+
     fn rendering_options(&mut self, ui: &mut Ui, ctx: &egui::Context) {
         let mut changed = false;
         let mut pad_toggled = false;
@@ -485,10 +819,62 @@ impl WalksnailOsdTool {
 
                         ui.label("Chroma key").on_hover_text(tooltip_text("Render the video with a chroma key instead of the input video so the OSD can be overlay in video editing software."));
                         ui.horizontal(|ui| {
-                            changed |= ui.add(Checkbox::without_text(&mut self.render_settings.use_chroma_key)).changed();
-                            changed |= ui.color_edit_button_rgb(&mut self.render_settings.chroma_key).changed();
+                            ui.add_enabled_ui(!self.render_settings.output_format.is_transparent(), |ui| {
+                                changed |= ui.add(Checkbox::without_text(&mut self.render_settings.use_chroma_key)).changed();
+                                changed |= ui.color_edit_button_rgb(&mut self.render_settings.chroma_key).changed();
+                            });
                         });
                         ui.end_row();
+
+                        ui.label("Output format").on_hover_text(tooltip_text("MP4 renders the OSD on top of the video with the selected encoder. The other formats render only the OSD on a transparent background so it can be overlaid in video editing software."));
+                        let output_formats = [
+                            backend::ffmpeg::OutputFormat::Mp4,
+                            backend::ffmpeg::OutputFormat::ProRes4444,
+                            backend::ffmpeg::OutputFormat::Vp9Alpha,
+                            backend::ffmpeg::OutputFormat::PngSequence,
+                        ];
+                        let mut selected_output_format_idx = output_formats
+                            .iter()
+                            .position(|f| *f == self.render_settings.output_format)
+                            .unwrap_or(0);
+                        let output_format_selection = egui::ComboBox::from_id_source("output_format")
+                            .width(180.0)
+                            .show_index(
+                                ui,
+                                &mut selected_output_format_idx,
+                                output_formats.len(),
+                                |i| output_formats[i].to_string(),
+                            );
+                        if output_format_selection.changed() {
+                            self.render_settings.output_format = output_formats[selected_output_format_idx];
+                            changed |= true;
+                        }
+                        ui.end_row();
+
+                        ui.label("Audio").on_hover_text(tooltip_text("Copy the audio track of the input video into the rendered video, re-encode it to AAC or leave it out."));
+                        let audio_modes = [
+                            backend::ffmpeg::AudioMode::Copy,
+                            backend::ffmpeg::AudioMode::Aac,
+                            backend::ffmpeg::AudioMode::Disabled,
+                        ];
+                        let mut selected_audio_idx = match self.render_settings.audio {
+                            backend::ffmpeg::AudioMode::Copy => 0,
+                            backend::ffmpeg::AudioMode::Aac => 1,
+                            backend::ffmpeg::AudioMode::Disabled => 2,
+                        };
+                        let audio_selection = egui::ComboBox::from_id_source("audio")
+                            .width(100.0)
+                            .show_index(
+                                ui,
+                                &mut selected_audio_idx,
+                                audio_modes.len(),
+                                |i| audio_modes[i].to_string(),
+                            );
+                        if audio_selection.changed() {
+                            self.render_settings.audio = audio_modes[selected_audio_idx];
+                            changed |= true;
+                        }
+                        ui.end_row();
                     });
             });
 
@@ -500,6 +886,82 @@ impl WalksnailOsdTool {
             self.config_changed = Some(Instant::now());
         }
     }
+
+    fn render_queue(&mut self, ui: &mut Ui) {
+        CollapsingHeader::new(RichText::new("Render queue").heading())
+            .default_open(true)
+            .show_unindented(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if self.render_queue.is_running() {
+                        if ui.button("Stop queue").clicked() {
+                            self.render_queue.stop();
+                        }
+                    } else if ui
+                        .add_enabled(
+                            self.render_queue.has_pending_jobs() && self.render_status.is_not_in_progress(),
+                            Button::new("Start queue"),
+                        )
+                        .clicked()
+                    {
+                        self.render_queue.start();
+                    }
+
+                    if ui
+                        .button("Add videos")
+                        .on_hover_text(tooltip_text(
+                            "Queue videos with the OSD and SRT files next to them, using the current font and settings.",
+                        ))
+                        .clicked()
+                    {
+                        if let Some(file_handles) = rfd::FileDialog::new()
+                            .add_filter("Video files", &["mp4"])
+                            .pick_files()
+                        {
+                            self.add_videos_to_render_queue(&file_handles);
+                        }
+                    }
+
+                    if ui.button("Remove finished").clicked() {
+                        self.render_queue.remove_finished();
+                    }
+                });
+
+                if self.render_queue.jobs.is_empty() {
+                    ui.label(RichText::new("No videos queued").weak());
+                    return;
+                }
+
+                let mut remove_job = None;
+                Grid::new("render_queue").num_columns(3).striped(true).show(ui, |ui| {
+                    for (index, job) in self.render_queue.jobs.iter().enumerate() {
+                        ui.label(job.file_name())
+                            .on_hover_text(tooltip_text(&job.output_video.to_string_lossy()));
+                        match &job.render_status.status {
+                            Status::Idle => ui.label("Queued"),
+                            Status::InProgress { progress_pct, .. } => {
+                                ui.add(ProgressBar::new(*progress_pct).desired_width(150.0).show_percentage())
+                            }
+                            Status::Completed => ui.label("Done"),
+                            Status::Cancelled { .. } => ui.label("Cancelled"),
+                            Status::Error { error, .. } => ui
+                                .label(RichText::new("Error").color(Color32::RED))
+                                .on_hover_text(tooltip_text(error)),
+                        };
+                        if ui
+                            .add_enabled(job.render_status.is_not_in_progress(), Button::new("Remove"))
+                            .clicked()
+                        {
+                            remove_job = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(index) = remove_job {
+                    self.render_queue.remove(index);
+                }
+            });
+    }
+
     pub fn auto_center_horizontal(&mut self) {
         if let (Some(video_info), Some(osd_file), Some(_)) = (&self.video_info, &self.osd_file, &self.font_file) {
             let is_4_3 = (video_info.width as f32 / video_info.height as f32) < 1.5;
@@ -509,17 +971,13 @@ impl WalksnailOsdTool {
                 video_info.width
             };
             let base_char_size = get_character_size(effective_width, video_info.height);
-            let scale_factor = self.osd_options.scale / 100.0;
-            let scaled_char_width = (base_char_size.width() as f32 * scale_factor).round() as i32;
+            let (scaled_char_width, _) = scaled_character_size(&base_char_size, &osd_file.grid_size, &self.osd_options);
 
             let frame = osd_file
                 .frames
                 .get(self.osd_preview.preview_frame as usize - 1)
                 .unwrap();
-            let min_x = frame.glyphs.iter().map(|g| g.grid_position.x).min().unwrap() as i32;
-            let max_x = frame.glyphs.iter().map(|g| g.grid_position.x).max().unwrap() as i32;
-            let pixel_range = (max_x - min_x + 1) * scaled_char_width;
-            self.osd_options.position.x = (video_info.width as i32 - pixel_range) / 2 - min_x * scaled_char_width;
+            self.osd_options.position.x = calculate_horizontal_offset(video_info.width, frame, scaled_char_width);
         }
     }
 }
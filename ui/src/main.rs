@@ -16,6 +16,7 @@ mod app;
 mod bottom_panel;
 mod central_panel;
 mod osd_preview;
+mod render_queue;
 mod render_status;
 mod side_panel;
 mod top_panel;
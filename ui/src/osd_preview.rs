@@ -1,20 +1,21 @@
 use backend::{
     font,
-    osd::{self, OsdOptions},
+    osd::{self, FlightState, OsdOptions},
     overlay::{overlay_osd, overlay_srt_data},
     srt::{self, SrtOptions},
 };
 use image::RgbaImage;
 
-#[tracing::instrument(skip(osd_frame, srt_frame, font), level = "debug")]
+#[tracing::instrument(skip(osd_frame_and_font, srt_frame), level = "debug")]
 pub fn create_osd_preview(
     width: u32,
     height: u32,
-    osd_frame: &osd::Frame,
+    osd_frame_and_font: Option<(&osd::Frame, &font::FontFile)>,
+    osd_grid_size: &osd::GridSize,
     srt_frame: Option<&srt::SrtFrame>,
-    font: &font::FontFile,
     srt_font: &rusttype::Font,
     osd_options: &OsdOptions,
+    flight_state: &FlightState,
     srt_options: &SrtOptions,
     pad_4_3_to_16_9: bool,
 ) -> RgbaImage {
@@ -45,7 +46,17 @@ pub fn create_osd_preview(
     // But Render is usually on top of video.
     // For preview, we just want to ensure OSD/SRT are positioned correctly.
 
-    overlay_osd(&mut image, osd_frame, font, osd_options, (x_offset as i32, 0));
+    if let Some((osd_frame, font)) = osd_frame_and_font {
+        overlay_osd(
+            &mut image,
+            osd_frame,
+            osd_grid_size,
+            font,
+            osd_options,
+            flight_state,
+            (x_offset as i32, 0),
+        );
+    }
     if let Some(srt_frame) = srt_frame {
         if let Some(srt_data) = &srt_frame.data {
             overlay_srt_data(&mut image, srt_data, srt_font, srt_options, (x_offset as i32, 0));
@@ -55,20 +66,23 @@ pub fn create_osd_preview(
     image
 }
 
+/// Horizontal OSD position that centers the glyphs of the frame. `char_width` is the width of the characters on the
+/// video, see [`backend::overlay::scaled_character_size`].
 #[tracing::instrument(level = "debug")]
-pub fn calculate_horizontal_offset(width: u32, osd_frame: &osd::Frame, character_size: &font::CharacterSize) -> i32 {
+pub fn calculate_horizontal_offset(width: u32, osd_frame: &osd::Frame, char_width: u32) -> i32 {
     let min_x_grid = osd_frame.glyphs.iter().map(|g| g.grid_position.x).min().unwrap() as i32;
     let max_x_grid = osd_frame.glyphs.iter().map(|g| g.grid_position.x).max().unwrap() as i32;
-    let char_width = character_size.width() as i32;
+    let char_width = char_width as i32;
     let pixel_range = (max_x_grid - min_x_grid + 1) * char_width;
     (width as i32 - pixel_range) / 2 - min_x_grid * char_width
 }
 
+/// Vertical OSD position that centers the glyphs of the frame.
 #[tracing::instrument(level = "debug")]
-pub fn calculate_vertical_offset(height: u32, osd_frame: &osd::Frame, character_size: &font::CharacterSize) -> i32 {
+pub fn calculate_vertical_offset(height: u32, osd_frame: &osd::Frame, char_height: u32) -> i32 {
     let min_y_grid = osd_frame.glyphs.iter().map(|g| g.grid_position.y).min().unwrap() as i32;
     let max_y_grid = osd_frame.glyphs.iter().map(|g| g.grid_position.y).max().unwrap() as i32;
-    let char_height = character_size.height() as i32;
+    let char_height = char_height as i32;
     let pixel_range = (max_y_grid - min_y_grid + 1) * char_height;
     (height as i32 - pixel_range) / 2 - min_y_grid * char_height
 }
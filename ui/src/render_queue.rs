@@ -0,0 +1,214 @@
+use std::path::{Path, PathBuf};
+
+use backend::{
+    ffmpeg::{start_video_render, FromFfmpegMessage, RenderSettings, ToFfmpegMessage, VideoInfo},
+    font::FontFile,
+    osd::{self, OsdFile, OsdOptions},
+    srt::{SrtFile, SrtFrame, SrtOptions},
+    util::get_output_video_path,
+};
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::render_status::{RenderStatus, Status};
+
+/// A single video in the render queue. All files and settings are copied when the job is created so changes made in
+/// the UI afterwards don't affect queued jobs.
+pub struct RenderJob {
+    pub input_video: PathBuf,
+    pub output_video: PathBuf,
+    pub video_info: VideoInfo,
+    pub osd_frames: Vec<osd::Frame>,
+    pub osd_grid_size: osd::GridSize,
+    pub srt_frames: Vec<SrtFrame>,
+    pub font_file: Option<FontFile>,
+    pub osd_options: OsdOptions,
+    pub srt_options: SrtOptions,
+    pub render_settings: RenderSettings,
+    pub render_status: RenderStatus,
+}
+
+impl RenderJob {
+    pub fn new(
+        input_video: &Path,
+        video_info: &VideoInfo,
+        osd_file: Option<&OsdFile>,
+        srt_file: Option<&SrtFile>,
+        font_file: Option<&FontFile>,
+        osd_options: &OsdOptions,
+        srt_options: &SrtOptions,
+        render_settings: &RenderSettings,
+    ) -> Self {
+        let mut osd_options = osd_options.clone();
+        osd_options.osd_playback_speed_factor = match osd_file {
+            Some(osd_file) if osd_options.adjust_playback_speed => {
+                video_info.duration.as_secs_f32() / osd_file.duration.as_secs_f32()
+            }
+            _ => 1.0,
+        };
+
+        Self {
+            input_video: input_video.to_path_buf(),
+            output_video: get_output_video_path(input_video, render_settings.output_format),
+            video_info: video_info.clone(),
+            osd_frames: osd_file.map(|o| o.frames.clone()).unwrap_or_default(),
+            osd_grid_size: osd_file.map_or(osd::GridSize::HD, |o| o.grid_size),
+            srt_frames: srt_file.map(|s| s.frames.clone()).unwrap_or_default(),
+            font_file: font_file.cloned(),
+            osd_options,
+            srt_options: srt_options.clone(),
+            render_settings: render_settings.clone(),
+            render_status: RenderStatus::default(),
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        self.input_video
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+struct ActiveJob {
+    index: usize,
+    to_ffmpeg_sender: Sender<ToFfmpegMessage>,
+    from_ffmpeg_receiver: Receiver<FromFfmpegMessage>,
+}
+
+/// Renders the queued jobs one after another.
+#[derive(Default)]
+pub struct RenderQueue {
+    pub jobs: Vec<RenderJob>,
+    running: bool,
+    active_job: Option<ActiveJob>,
+}
+
+impl RenderQueue {
+    pub fn push(&mut self, job: RenderJob) {
+        tracing::info!("Added {} to the render queue", job.input_video.display());
+        self.jobs.push(job);
+    }
+
+    /// Remove a job from the queue. The job that is currently rendering can't be removed.
+    pub fn remove(&mut self, index: usize) {
+        if let Some(active_job) = &mut self.active_job {
+            if active_job.index == index {
+                return;
+            }
+            if active_job.index > index {
+                active_job.index -= 1;
+            }
+        }
+        self.jobs.remove(index);
+    }
+
+    pub fn remove_finished(&mut self) {
+        let mut index = 0;
+        while index < self.jobs.len() {
+            if matches!(self.jobs[index].render_status.status, Status::Completed) {
+                self.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn has_pending_jobs(&self) -> bool {
+        self.jobs
+            .iter()
+            .any(|job| matches!(job.render_status.status, Status::Idle))
+    }
+
+    pub fn start(&mut self) {
+        tracing::info!("Starting render queue");
+        self.running = true;
+    }
+
+    /// Stop the queue and cancel the job that is currently rendering.
+    pub fn stop(&mut self) {
+        tracing::info!("Stopping render queue");
+        self.running = false;
+        if let Some(active_job) = self.active_job.take() {
+            active_job
+                .to_ffmpeg_sender
+                .send(ToFfmpegMessage::AbortRender)
+                .map_err(|_| tracing::warn!("Failed to send abort render message"))
+                .ok();
+            self.jobs[active_job.index].render_status.stop_render();
+        }
+    }
+
+    /// Process messages from the job that is rendering and start the next job when it's done. Should be called on
+    /// every UI update.
+    pub fn update(&mut self, ffmpeg_path: &PathBuf, srt_font: &rusttype::Font<'static>) {
+        if let Some(active_job) = &self.active_job {
+            let job = &mut self.jobs[active_job.index];
+            let total_frames = job.render_settings.frames_to_render(&job.video_info);
+            while let Ok(message) = active_job.from_ffmpeg_receiver.try_recv() {
+                if matches!(
+                    message,
+                    FromFfmpegMessage::EncoderFatalError(_) | FromFfmpegMessage::EncoderFinished
+                ) {
+                    active_job.to_ffmpeg_sender.send(ToFfmpegMessage::AbortRender).ok();
+                }
+                job.render_status.update_from_ffmpeg_message(message, total_frames);
+            }
+
+            if job.render_status.is_not_in_progress() {
+                self.active_job = None;
+            }
+        }
+
+        if self.running && self.active_job.is_none() {
+            match self
+                .jobs
+                .iter()
+                .position(|job| matches!(job.render_status.status, Status::Idle))
+            {
+                Some(index) => self.start_job(index, ffmpeg_path, srt_font),
+                None => {
+                    tracing::info!("Render queue finished");
+                    self.running = false;
+                }
+            }
+        }
+    }
+
+    fn start_job(&mut self, index: usize, ffmpeg_path: &PathBuf, srt_font: &rusttype::Font<'static>) {
+        let job = &mut self.jobs[index];
+        tracing::info!("Starting queued render of {}", job.input_video.display());
+        job.render_status.start_render();
+        match start_video_render(
+            ffmpeg_path,
+            Some(&job.input_video),
+            &job.output_video,
+            job.osd_frames.clone(),
+            job.osd_grid_size,
+            job.srt_frames.clone(),
+            job.font_file.clone(),
+            srt_font.clone(),
+            &job.osd_options,
+            &job.srt_options,
+            &job.video_info,
+            &job.render_settings,
+        ) {
+            Ok((to_ffmpeg_sender, from_ffmpeg_receiver)) => {
+                self.active_job = Some(ActiveJob {
+                    index,
+                    to_ffmpeg_sender,
+                    from_ffmpeg_receiver,
+                });
+            }
+            Err(e) => {
+                job.render_status.status = Status::Error {
+                    progress_pct: 0.0,
+                    error: format!("Failed to start video render: {}", e),
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use backend::ffmpeg::{FromFfmpegMessage, VideoInfo};
+use backend::ffmpeg::FromFfmpegMessage;
 
 #[derive(Default)]
 pub struct RenderStatus {
@@ -58,7 +58,7 @@ impl RenderStatus {
         }
     }
 
-    pub fn update_from_ffmpeg_message(&mut self, message: FromFfmpegMessage, video_info: &VideoInfo) {
+    pub fn update_from_ffmpeg_message(&mut self, message: FromFfmpegMessage, total_frames: u32) {
         match (&self.status, &message) {
             (
                 Status::InProgress { progress_pct, .. },
@@ -72,7 +72,7 @@ impl RenderStatus {
 
             (Status::InProgress { .. }, FromFfmpegMessage::Progress(p)) => {
                 let frame = p.frame as f32;
-                let total_frames = video_info.total_frames as f32;
+                let total_frames = total_frames as f32;
                 let progress_pct = frame / total_frames;
                 let frames_remaining = total_frames - frame;
                 let time_remaining_secs = frames_remaining / p.fps;
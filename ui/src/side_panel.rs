@@ -1,9 +1,9 @@
-use backend::font::FontType;
-use egui::{CollapsingHeader, RichText, Ui};
+use backend::{font::FontType, osd::GridSize};
+use egui::{Button, CollapsingHeader, RichText, Ui};
 use egui_extras::{Column, TableBuilder};
 
 use super::WalksnailOsdTool;
-use crate::util::{format_minutes_seconds, separator_with_space};
+use crate::util::{format_minutes_seconds, separator_with_space, tooltip_text};
 
 impl WalksnailOsdTool {
     pub fn render_sidepanel(&mut self, ctx: &egui::Context) {
@@ -152,6 +152,31 @@ impl WalksnailOsdTool {
                                 });
                             });
 
+                            body.row(row_height, |mut row| {
+                                row.col(|ui| {
+                                    ui.label("Format:");
+                                });
+                                row.col(|ui| {
+                                    let format = match osd_file {
+                                        Some(osd_file) => match osd_file.format_version {
+                                            Some(version) => format!("{} (version {})", osd_file.format, version),
+                                            None => osd_file.format.to_string(),
+                                        },
+                                        None => "-".into(),
+                                    };
+                                    ui.label(format);
+                                });
+                            });
+
+                            body.row(row_height, |mut row| {
+                                row.col(|ui| {
+                                    ui.label("Grid:");
+                                });
+                                row.col(|ui| {
+                                    ui.label(osd_file.map(|o| o.grid_size.to_string()).unwrap_or("-".into()));
+                                });
+                            });
+
                             body.row(row_height, |mut row| {
                                 row.col(|ui| {
                                     ui.label("FC firmware:");
@@ -178,6 +203,20 @@ impl WalksnailOsdTool {
                                 });
                             });
 
+                            if let Some(discarded) = osd_file.map(|o| o.discarded).filter(|d| !d.is_empty()) {
+                                body.row(row_height, |mut row| {
+                                    row.col(|ui| {
+                                        ui.label("Discarded:");
+                                    });
+                                    row.col(|ui| {
+                                        ui.colored_label(ui.visuals().warn_fg_color, discarded.to_string())
+                                            .on_hover_text(tooltip_text(
+                                                "The OSD file is damaged, e.g. because the goggles lost power while recording. These frames and bytes were skipped, the rest of the file was loaded.",
+                                            ));
+                                    });
+                                });
+                            }
+
                             body.row(row_height, |mut row| {
                                 row.col(|ui| {
                                     ui.label("Duration:");
@@ -192,6 +231,42 @@ impl WalksnailOsdTool {
                             });
                         });
                 });
+
+                ui.add_enabled_ui(file_loaded, |ui| {
+                    ui.horizontal(|ui| {
+                        let hd_grid = osd_file.is_some_and(|o| o.grid_size == GridSize::HD);
+                        if ui
+                            .add_enabled(hd_grid, Button::new("Save as .osd"))
+                            .on_hover_text(tooltip_text(
+                                "Save the OSD data as a Walksnail .osd file, e.g. to keep OSD data extracted from an Artlynk video.",
+                            ))
+                            .on_disabled_hover_text(tooltip_text(
+                                "The .osd format only stores the Walksnail grid, this OSD has another grid size.",
+                            ))
+                            .clicked()
+                        {
+                            self.export_osd_file();
+                        }
+                        if ui
+                            .button("Export telemetry")
+                            .on_hover_text(tooltip_text(
+                                "Save the values read from the OSD, together with the SRT data, as a CSV or JSON file for spreadsheets and analysis tools.",
+                            ))
+                            .clicked()
+                        {
+                            self.export_telemetry();
+                        }
+                        if ui
+                            .button("Export flight path")
+                            .on_hover_text(tooltip_text(
+                                "Save the GPS coordinates shown on the OSD as a GPX or KML track for mapping tools.",
+                            ))
+                            .clicked()
+                        {
+                            self.export_flight_path();
+                        }
+                    });
+                });
             });
     }
 
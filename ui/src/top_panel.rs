@@ -71,11 +71,13 @@ impl WalksnailOsdTool {
         {
             self.video_file = None;
             self.video_info = None;
+            self.discard_artlynk_extraction();
             self.osd_file = None;
             self.font_file = None;
             self.srt_file = None;
             self.osd_preview.texture_handle = None;
             self.osd_preview.preview_frame = 1;
+            self.render_settings.time_range = None;
             self.render_status.reset();
             tracing::info!("Reset files");
         }
@@ -1,14 +1,18 @@
 use std::{
     env::current_exe,
-    path::{Path, PathBuf},
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
 use backend::{
     config::AppConfig,
-    ffmpeg::VideoInfo,
+    export::{telemetry_records, write_telemetry, write_track, TelemetryFormat, TrackFormat},
+    ffmpeg::{RenderSettings, VideoInfo},
     font::FontFile,
-    osd::OsdFile,
+    osd::{
+        artlynk::{self, ExtractionHandle},
+        OsdSourceRegistry, SourceContext,
+    },
     srt::{SrtFile, SrtOptions},
 };
 use egui::{FontFamily, FontId, Margin, RichText, Separator, TextStyle, Ui};
@@ -19,13 +23,18 @@ use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::{filter, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, Layer};
 
 use super::WalksnailOsdTool;
-use crate::util::build_info::Build;
+use crate::{render_queue::RenderJob, util::build_info::Build};
 
 impl WalksnailOsdTool {
     pub fn all_files_loaded(&self) -> bool {
         self.video_loaded() && self.osd_loaded() && self.font_loaded()
     }
 
+    /// A video with either the OSD and a font or SRT data to render on top of it.
+    pub fn ready_to_render(&self) -> bool {
+        self.video_loaded() && ((self.osd_loaded() && self.font_loaded()) || self.srt_loaded())
+    }
+
     pub fn video_loaded(&self) -> bool {
         self.video_file.is_some() && self.video_info.is_some()
     }
@@ -44,8 +53,10 @@ impl WalksnailOsdTool {
 
     pub fn import_video_file(&mut self, file_handles: &[PathBuf]) {
         if let Some(video_file) = filter_file_with_extention(file_handles, "mp4") {
+            self.discard_artlynk_extraction();
             self.video_file = Some(video_file.clone());
             self.video_info = VideoInfo::get(video_file, &self.dependencies.ffprobe_path).ok();
+            self.render_settings.time_range = None;
 
             if let Some(video_info) = &self.video_info {
                 self.render_settings.bitrate_mbps = (video_info.bitrate as f32 / 1_000_000.0).round() as u32;
@@ -57,23 +68,142 @@ impl WalksnailOsdTool {
 
             // If no .osd file was loaded, try Artlynk extraction from video SEI data
             if self.osd_file.is_none() {
-                let ffmpeg_path = self.dependencies.ffmpeg_path.clone();
                 let video_path = video_file.clone();
+                let handle = ExtractionHandle::new(self.video_info.as_ref().map(|i| i.duration).unwrap_or_default());
+                let extraction_handle = handle.clone();
+
+                let context = SourceContext {
+                    ffmpeg_path: self.dependencies.ffmpeg_path.clone(),
+                    lenient: true,
+                    cache_dir: artlynk::default_cache_dir(),
+                    handle,
+                };
 
                 self.artlynk_extraction_promise = Some(Promise::spawn_thread("Artlynk extraction", move || {
-                    backend::osd::artlynk::extract_osd_from_video(&ffmpeg_path, &video_path)
+                    OsdSourceRegistry::default().open(&video_path, &context)
                 }));
+                self.artlynk_extraction_handle = Some(extraction_handle);
             }
         }
     }
 
+    /// Stop a running Artlynk extraction and throw away its result, e.g. because another file was loaded.
+    pub fn discard_artlynk_extraction(&mut self) {
+        if let Some(handle) = self.artlynk_extraction_handle.take() {
+            tracing::info!("Discarding Artlynk OSD extraction");
+            handle.cancel();
+        }
+        self.artlynk_extraction_promise = None;
+    }
+
     pub fn import_osd_file(&mut self, file_handles: &[PathBuf]) {
         if let Some(osd_file_path) = filter_file_with_extention(file_handles, "osd") {
-            self.osd_file = OsdFile::open(osd_file_path.clone()).ok();
+            self.discard_artlynk_extraction();
+            self.osd_file = OsdSourceRegistry::default()
+                .open(osd_file_path, &self.osd_source_context())
+                .ok();
             self.osd_preview.preview_frame = 1;
         }
     }
 
+    /// Damaged OSD files are opened with the frames that were recorded completely, the discarded data is shown in the
+    /// OSD file panel.
+    fn osd_source_context(&self) -> SourceContext {
+        SourceContext {
+            ffmpeg_path: self.dependencies.ffmpeg_path.clone(),
+            lenient: true,
+            ..Default::default()
+        }
+    }
+
+    /// Ask where to save the loaded OSD data and write it as an `.osd` file.
+    pub fn export_osd_file(&self) {
+        let Some(osd_file) = &self.osd_file else {
+            return;
+        };
+
+        let mut dialog = rfd::FileDialog::new().add_filter("OSD files", &["osd"]);
+        if let Some(parent) = osd_file.file_path.parent() {
+            dialog = dialog.set_directory(parent);
+        }
+        if let Some(stem) = osd_file.file_path.file_stem() {
+            dialog = dialog.set_file_name(format!("{}.osd", stem.to_string_lossy()));
+        }
+
+        if let Some(path) = dialog.save_file() {
+            match osd_file.write(&path) {
+                Ok(()) => tracing::info!("Saved OSD data to {}", path.display()),
+                Err(e) => tracing::error!("Failed to save OSD data: {}", e),
+            }
+        }
+    }
+
+    /// Ask where to save the telemetry of the loaded OSD data and write it as CSV or JSON, depending on the extension.
+    pub fn export_telemetry(&self) {
+        let Some(osd_file) = &self.osd_file else {
+            return;
+        };
+
+        let mut dialog = rfd::FileDialog::new()
+            .add_filter("CSV files", &["csv"])
+            .add_filter("JSON files", &["json"]);
+        if let Some(parent) = osd_file.file_path.parent() {
+            dialog = dialog.set_directory(parent);
+        }
+        if let Some(stem) = osd_file.file_path.file_stem() {
+            dialog = dialog.set_file_name(format!("{}_telemetry.csv", stem.to_string_lossy()));
+        }
+
+        if let Some(path) = dialog.save_file() {
+            // Same correction as when rendering, so the OSD values line up with the SRT data
+            let osd_playback_speed_factor = match &self.video_info {
+                Some(video_info) if self.osd_options.adjust_playback_speed => {
+                    video_info.duration.as_secs_f32() / osd_file.duration.as_secs_f32()
+                }
+                _ => 1.0,
+            };
+            let records = telemetry_records(osd_file, self.srt_file.as_ref(), osd_playback_speed_factor);
+            match write_telemetry(&records, &path, TelemetryFormat::from_path(&path)) {
+                Ok(()) => tracing::info!("Saved telemetry to {}", path.display()),
+                Err(e) => tracing::error!("Failed to save telemetry: {}", e),
+            }
+        }
+    }
+
+    /// Ask where to save the GPS track of the loaded OSD data and write it as GPX or KML, depending on the extension.
+    pub fn export_flight_path(&self) {
+        let Some(osd_file) = &self.osd_file else {
+            return;
+        };
+
+        let mut dialog = rfd::FileDialog::new()
+            .add_filter("GPX files", &["gpx"])
+            .add_filter("KML files", &["kml"]);
+        if let Some(parent) = osd_file.file_path.parent() {
+            dialog = dialog.set_directory(parent);
+        }
+        let name = osd_file
+            .file_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        dialog = dialog.set_file_name(format!("{name}.gpx"));
+
+        if let Some(path) = dialog.save_file() {
+            let track = osd_file.flight_path();
+            match write_track(
+                &track,
+                &name,
+                osd_file.recording_start(),
+                &path,
+                TrackFormat::from_path(&path),
+            ) {
+                Ok(()) => tracing::info!("Saved flight path to {}", path.display()),
+                Err(e) => tracing::error!("Failed to save flight path: {}", e),
+            }
+        }
+    }
+
     pub fn import_srt_file(&mut self, file_handles: &[PathBuf]) {
         if let Some(srt_file_path) = filter_file_with_extention(file_handles, "srt") {
             self.srt_file = SrtFile::open(srt_file_path.clone()).ok();
@@ -95,6 +225,70 @@ impl WalksnailOsdTool {
         }
     }
 
+    /// Queue the loaded files with the current settings.
+    pub fn add_to_render_queue(&mut self) {
+        if let (Some(video_file), Some(video_info), true) = (&self.video_file, &self.video_info, self.ready_to_render())
+        {
+            self.render_queue.push(RenderJob::new(
+                video_file,
+                video_info,
+                self.osd_file.as_ref(),
+                self.srt_file.as_ref(),
+                self.font_file.as_ref(),
+                &self.osd_options,
+                &self.srt_options,
+                &self.render_settings,
+            ));
+        }
+    }
+
+    /// Queue videos together with the OSD and SRT files next to them, using the current font and settings. The OSD is
+    /// only rendered when a font is loaded and videos without OSD or SRT data are skipped.
+    pub fn add_videos_to_render_queue(&mut self, file_handles: &[PathBuf]) {
+        // In and out points only make sense for the video they were set on
+        let render_settings = RenderSettings {
+            time_range: None,
+            ..self.render_settings.clone()
+        };
+
+        for video_file in file_handles
+            .iter()
+            .filter(|f| f.extension().is_some_and(|e| e.eq_ignore_ascii_case("mp4")))
+        {
+            let video_info = match VideoInfo::get(video_file, &self.dependencies.ffprobe_path) {
+                Ok(video_info) => video_info,
+                Err(e) => {
+                    tracing::warn!("Not queueing {}: {}", video_file.display(), e);
+                    continue;
+                }
+            };
+            let osd_file = self.font_file.as_ref().and_then(|_| {
+                OsdSourceRegistry::default()
+                    .open(
+                        &matching_file_with_extension(video_file, "osd"),
+                        &self.osd_source_context(),
+                    )
+                    .ok()
+            });
+            let srt_file = SrtFile::open(matching_file_with_extension(video_file, "srt")).ok();
+            if osd_file.is_none() && srt_file.is_none() {
+                tracing::warn!("Not queueing {}: no OSD or SRT data found", video_file.display());
+                continue;
+            }
+
+            self.render_queue.push(RenderJob::new(
+                video_file,
+                &video_info,
+                osd_file.as_ref(),
+                srt_file.as_ref(),
+                self.font_file.as_ref(),
+                &self.osd_options,
+                &self.srt_options,
+                &render_settings,
+            ));
+        }
+    }
+
     pub fn import_font_file(&mut self, file_handles: &[PathBuf]) {
         if let Some(font_file_path) = filter_file_with_extention(file_handles, "png") {
             self.font_file = FontFile::open(font_file_path.clone()).ok();
@@ -135,14 +329,6 @@ pub fn format_minutes_seconds(duration: &Duration) -> String {
     format!("{}:{:0>2}", minutes, seconds)
 }
 
-pub fn get_output_video_path(input_video_path: &Path) -> PathBuf {
-    let input_video_file_name = input_video_path.file_stem().unwrap().to_string_lossy();
-    let output_video_file_name = format!("{}_with_osd.mp4", input_video_file_name);
-    let mut output_video_path = input_video_path.parent().unwrap().to_path_buf();
-    output_video_path.push(output_video_file_name);
-    output_video_path
-}
-
 pub fn set_style(ctx: &egui::Context) {
     use egui::{
         FontFamily::{Monospace, Proportional},